@@ -1,147 +1,675 @@
-use std::collections::HashMap;
+use std::{io, ops::Range, path::PathBuf};
 
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
     prelude::Backend,
     style::{Color, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Widget, Wrap},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, Paragraph, StatefulWidget, Tabs, Widget, Wrap,
+    },
     Terminal,
 };
-use thiserror::Error;
+use serde_json::Value;
 
-#[derive(Debug, Error)]
-pub enum Error {
-    #[error("IO error: {0}")]
-    IO(#[from] std::io::Error),
-}
-
-type Result<T> = std::result::Result<T, Error>;
+use crate::{
+    diff::{changes, diff, Change, DiffRow},
+    document::{Document, Pointer, Row, Segment},
+    error::{Error, Result},
+    export::{Export, Format},
+    history::Command,
+    query::Query,
+    schema::{Schema, Violation},
+    search::{scalar_text, Matcher},
+    text_input::TextInput,
+    value_type::ValueType,
+};
 
 #[derive(PartialEq, Eq)]
 pub enum CurrentScreen {
     Main,
     Editing,
+    Deleting,
     Exiting,
+    Searching,
+    Querying,
+    Exporting,
+    Diffing,
 }
 
 pub enum CurrentlyEditing {
     Key,
+    Type,
     Value,
 }
 
+/// The field of the export dialog taking the arrow keys and typing.
+#[derive(PartialEq, Eq)]
+pub enum ExportField {
+    Format,
+    Indent,
+    Destination,
+}
+
+/// A copied node, along with its key when it was an object member.
+pub struct Clipboard {
+    pub key: Option<String>,
+    pub value: Value,
+}
+
+/// A document to write to stdout once the terminal is restored.
+#[derive(Clone)]
+pub struct Output {
+    pub export: Export,
+    /// Index of the exported tab, rendered as it is at exit rather than when it was exported.
+    pub document: usize,
+}
+
+impl Output {
+    pub fn render(&self, documents: &[Document]) -> Result<String> {
+        self.export.render(&documents[self.document].sorted_value())
+    }
+}
+
+/// What the editing popup writes back to when it completes.
+pub enum EditTarget {
+    /// Adds a new child to the container at this pointer.
+    Insert(Pointer),
+    /// Replaces the node at this pointer, renaming it when its key changed.
+    Update(Pointer),
+}
+
 pub struct App {
-    pub key_input: String,
-    pub value_input: String,
-    pub pairs: HashMap<String, String>,
+    pub key_input: TextInput,
+    pub value_input: TextInput,
+    pub search_input: TextInput,
+    /// Whether the search is a regular expression rather than a plain substring.
+    pub search_regex: bool,
+    pub search_error: Option<String>,
+    /// The JSON pointer or jq-like path typed in the `:` command bar.
+    pub query_input: TextInput,
+    pub query_error: Option<String>,
+    pub value_type: ValueType,
+    /// The open documents, one per tab.
+    pub documents: Vec<Document>,
+    /// Index of the document in the selected tab.
+    pub current: usize,
+    /// The node last copied with `y`, to be pasted into any open document.
+    pub clipboard: Option<Clipboard>,
+    pub schema: Option<Schema>,
+    pub violations: Vec<Violation>,
+    pub show_violations: bool,
+    pub edit_target: EditTarget,
     pub current_screen: CurrentScreen,
     pub currently_editing: Option<CurrentlyEditing>,
+    pub export: Export,
+    pub export_field: ExportField,
+    /// The file typed in the export dialog, stdout when left empty.
+    pub export_input: TextInput,
+    pub export_error: Option<String>,
+    /// Whether the export dialog was opened from the exit popup.
+    pub quit_after_export: bool,
+    /// First line of the diff view on screen.
+    pub diff_scroll: u16,
+    /// A failure to show in a popup until the next key press.
+    pub error: Option<Error>,
+    pub should_quit: bool,
+    /// Whether the document was piped in on stdin and goes back out on stdout.
+    pub pipeline: bool,
+    /// Written to stdout once the terminal is restored.
+    pub output: Option<Output>,
 }
 
 impl App {
     pub fn default() -> App {
         App {
-            key_input: String::new(),
-            value_input: String::new(),
-            pairs: HashMap::new(),
+            key_input: TextInput::default(),
+            value_input: TextInput::default(),
+            search_input: TextInput::default(),
+            search_regex: false,
+            search_error: None,
+            query_input: TextInput::default(),
+            query_error: None,
+            value_type: ValueType::default(),
+            documents: vec![Document::default()],
+            current: 0,
+            clipboard: None,
+            schema: None,
+            violations: Vec::new(),
+            show_violations: false,
+            edit_target: EditTarget::Insert(Pointer::new()),
             current_screen: CurrentScreen::Main,
             currently_editing: None,
+            export: Export::default(),
+            export_field: ExportField::Format,
+            export_input: TextInput::default(),
+            export_error: None,
+            quit_after_export: false,
+            diff_scroll: 0,
+            error: None,
+            should_quit: false,
+            pipeline: false,
+            output: None,
         }
     }
 
-    pub fn run<B: Backend>(mut self, terminal: &mut Terminal<B>) -> Result<()> {
-        while self.current_screen != CurrentScreen::Exiting {
-            terminal.draw(|frame| frame.render_widget(&mut self, frame.area()))?;
-            self.event_handlers()?;
+    /// Opens each file in its own tab.
+    pub fn open(paths: Vec<PathBuf>) -> Result<App> {
+        Ok(App {
+            documents: paths
+                .into_iter()
+                .map(Document::open)
+                .collect::<Result<_>>()?,
+            ..App::default()
+        })
+    }
+
+    /// Edits the document piped in on stdin, to be written to stdout when confirmed.
+    pub fn read_stdin() -> Result<App> {
+        Ok(App {
+            documents: vec![Document::read(io::stdin().lock(), "stdin")?],
+            pipeline: true,
+            ..App::default()
+        })
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.documents[self.current]
+    }
+
+    pub fn document_mut(&mut self) -> &mut Document {
+        &mut self.documents[self.current]
+    }
+
+    /// Validates the document against the schema at `path` from now on.
+    pub fn load_schema(&mut self, path: PathBuf) -> Result<()> {
+        self.schema = Some(Schema::open(path)?);
+        self.show_violations = true;
+        self.validate();
+        Ok(())
+    }
+
+    fn validate(&mut self) {
+        if let Some(schema) = &self.schema {
+            self.violations = schema.validate(self.document());
+        }
+    }
+
+    /// Runs until the user quits, only failing when the terminal itself does.
+    ///
+    /// Anything going wrong while handling an event, such as a file which cannot be saved,
+    /// is reported in the error popup and the editor carries on.
+    pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        while !self.should_quit {
+            terminal.draw(|frame| frame.render_widget(&mut *self, frame.area()))?;
+            let event = event::read()?;
+            if let Err(err) = self.event_handlers(event) {
+                self.error = Some(err);
+            }
         }
 
         Ok(())
     }
 
-    fn event_handlers(&mut self) -> Result<()> {
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                match self.current_screen {
-                    CurrentScreen::Main => match key.code {
-                        KeyCode::Char('e') => {
-                            self.current_screen = CurrentScreen::Editing;
-                            self.currently_editing = Some(CurrentlyEditing::Key);
+    fn event_handlers(&mut self, event: Event) -> Result<()> {
+        if self.error.is_some() {
+            if let Event::Key(key) = event {
+                if key.kind == KeyEventKind::Press {
+                    self.error = None;
+                }
+            }
+            return Ok(());
+        }
+
+        match event {
+            Event::Paste(text) => {
+                if let Some(input) = self.focused_input() {
+                    input.insert_str(&text);
+                }
+                if self.current_screen == CurrentScreen::Searching {
+                    self.update_search();
+                }
+                self.export_error = None;
+            }
+            Event::Key(key) if key.kind == KeyEventKind::Press => match self.current_screen {
+                CurrentScreen::Main => match key.code {
+                    KeyCode::Char('e') => self.start_editing(),
+                    KeyCode::Char('u') => {
+                        self.document_mut().undo();
+                        self.validate();
+                    }
+                    KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.document_mut().redo();
+                        self.validate();
+                    }
+                    KeyCode::Enter => self.start_updating(),
+                    KeyCode::Char('d') if !self.document().selected.is_empty() => {
+                        self.current_screen = CurrentScreen::Deleting
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => self.document_mut().select_previous(),
+                    KeyCode::Down | KeyCode::Char('j') => self.document_mut().select_next(),
+                    KeyCode::Left | KeyCode::Char('h') => {
+                        self.document_mut().collapse_or_select_parent()
+                    }
+                    KeyCode::Right | KeyCode::Char('l') => {
+                        self.document_mut().expand_or_select_child()
+                    }
+                    KeyCode::Char(' ') => self.document_mut().toggle_collapsed(),
+                    KeyCode::Char('o') => {
+                        self.document_mut().sort_mode = self.document().sort_mode.next()
+                    }
+                    KeyCode::Char('s') => self.document_mut().save()?,
+                    KeyCode::Char('v') if self.schema.is_some() => {
+                        self.show_violations = !self.show_violations
+                    }
+                    KeyCode::PageDown if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.switch_tab(true)
+                    }
+                    KeyCode::PageUp if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.switch_tab(false)
+                    }
+                    KeyCode::Char('y') => self.copy_selected(),
+                    KeyCode::Char('p') => self.paste(),
+                    KeyCode::Char('x') => self.start_exporting(false),
+                    KeyCode::Char('c') => {
+                        self.diff_scroll = 0;
+                        self.current_screen = CurrentScreen::Diffing;
+                    }
+                    KeyCode::Char('/') => {
+                        self.clear_query();
+                        self.current_screen = CurrentScreen::Searching;
+                    }
+                    KeyCode::Char(':') => {
+                        self.clear_search();
+                        self.clear_query();
+                        self.current_screen = CurrentScreen::Querying;
+                    }
+                    KeyCode::Char('n') => self.document_mut().select_next_match(),
+                    KeyCode::Char('N') => self.document_mut().select_previous_match(),
+                    KeyCode::Esc => {
+                        self.clear_search();
+                        self.clear_query();
+                    }
+                    KeyCode::Char('q') => self.request_quit(),
+                    _ => {}
+                },
+                CurrentScreen::Searching => match key.code {
+                    KeyCode::Enter => self.current_screen = CurrentScreen::Main,
+                    KeyCode::Esc => {
+                        self.clear_search();
+                        self.current_screen = CurrentScreen::Main;
+                    }
+                    KeyCode::Tab => {
+                        self.search_regex = !self.search_regex;
+                        self.update_search();
+                    }
+                    _ => {
+                        self.search_input.handle_key(key);
+                        self.update_search();
+                    }
+                },
+                CurrentScreen::Deleting => match key.code {
+                    KeyCode::Char('y') => {
+                        self.delete_selected();
+                        self.current_screen = CurrentScreen::Main;
+                    }
+                    KeyCode::Char('n') | KeyCode::Esc => self.current_screen = CurrentScreen::Main,
+                    _ => {}
+                },
+                CurrentScreen::Exiting => match key.code {
+                    KeyCode::Char('y') => {
+                        // An export confirmed earlier keeps the format and tab picked for it.
+                        let output = self.output.clone().or_else(|| {
+                            self.document().path.is_none().then(|| Output {
+                                export: Export {
+                                    format: Format::CompactJson,
+                                    ..Export::default()
+                                },
+                                document: self.current,
+                            })
+                        });
+                        // Edits made since the export may no longer fit its format.
+                        if let Some(output) = &output {
+                            output.render(&self.documents)?;
                         }
-                        KeyCode::Char('q') => self.current_screen = CurrentScreen::Exiting,
-                        _ => {}
-                    },
-                    CurrentScreen::Exiting => match key.code {
-                        KeyCode::Char('y') => self.current_screen = CurrentScreen::Exiting,
-                        _ => {}
-                    },
-                    CurrentScreen::Editing => match key.code {
-                        KeyCode::Enter => {
-                            if let Some(editing) = &self.currently_editing {
-                                match editing {
-                                    CurrentlyEditing::Key => {
-                                        self.currently_editing = Some(CurrentlyEditing::Value)
-                                    }
-                                    CurrentlyEditing::Value => {
-                                        self.save_key_value();
-                                        self.current_screen = CurrentScreen::Main;
-                                    }
-                                }
+                        for document in &mut self.documents {
+                            if document.dirty {
+                                document.save()?;
                             }
                         }
-                        KeyCode::Backspace => {
-                            if let Some(editing) = &self.currently_editing {
-                                match editing {
-                                    CurrentlyEditing::Key => {
-                                        self.key_input.pop();
-                                    }
-                                    CurrentlyEditing::Value => {
-                                        self.value_input.pop();
-                                    }
-                                }
-                            }
+                        self.output = output;
+                        self.should_quit = true;
+                    }
+                    KeyCode::Char('n') => {
+                        // An aborted pipeline writes nothing, not even an earlier export.
+                        if self.pipeline {
+                            self.output = None;
                         }
+                        self.should_quit = true;
+                    }
+                    KeyCode::Char('x') => self.start_exporting(true),
+                    KeyCode::Esc => self.current_screen = CurrentScreen::Main,
+                    _ => {}
+                },
+                CurrentScreen::Diffing => match key.code {
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.diff_scroll = self.diff_scroll.saturating_sub(1)
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        self.diff_scroll = self.diff_scroll.saturating_add(1)
+                    }
+                    KeyCode::PageUp => self.diff_scroll = self.diff_scroll.saturating_sub(10),
+                    KeyCode::PageDown => self.diff_scroll = self.diff_scroll.saturating_add(10),
+                    KeyCode::Esc | KeyCode::Char('c') | KeyCode::Char('q') => {
+                        self.current_screen = CurrentScreen::Main
+                    }
+                    _ => {}
+                },
+                CurrentScreen::Querying => {
+                    self.query_error = None;
+                    match key.code {
+                        KeyCode::Enter => self.run_query(),
                         KeyCode::Esc => {
+                            self.clear_query();
                             self.current_screen = CurrentScreen::Main;
-                            self.currently_editing = None;
                         }
-                        KeyCode::Char(value) => {
-                            if let Some(editing) = &self.currently_editing {
-                                match editing {
-                                    CurrentlyEditing::Key => {
-                                        self.key_input.push(value);
-                                    }
-                                    CurrentlyEditing::Value => {
-                                        self.value_input.push(value);
+                        _ => self.query_input.handle_key(key),
+                    }
+                }
+                CurrentScreen::Exporting => {
+                    self.export_error = None;
+                    match key.code {
+                        KeyCode::Enter => self.finish_exporting(),
+                        KeyCode::Esc => {
+                            self.current_screen = if self.quit_after_export {
+                                CurrentScreen::Exiting
+                            } else {
+                                CurrentScreen::Main
+                            }
+                        }
+                        KeyCode::Tab => self.toggle_export_field(),
+                        KeyCode::Left | KeyCode::Right
+                            if self.export_field != ExportField::Destination =>
+                        {
+                            self.change_export_setting(key.code == KeyCode::Right)
+                        }
+                        _ => {
+                            if let Some(input) = self.focused_input() {
+                                input.handle_key(key);
+                            }
+                        }
+                    }
+                }
+                CurrentScreen::Editing => match key.code {
+                    KeyCode::Enter => {
+                        if let Some(editing) = &self.currently_editing {
+                            match editing {
+                                CurrentlyEditing::Key | CurrentlyEditing::Type => {
+                                    self.currently_editing = Some(CurrentlyEditing::Value)
+                                }
+                                CurrentlyEditing::Value => {
+                                    if self.save_key_value() {
+                                        self.current_screen = CurrentScreen::Main;
                                     }
                                 }
                             }
                         }
-                        KeyCode::Tab => {
-                            self.toggle_editing();
+                    }
+                    KeyCode::Esc => {
+                        self.current_screen = CurrentScreen::Main;
+                        self.currently_editing = None;
+                    }
+                    KeyCode::Tab => {
+                        self.toggle_editing();
+                    }
+                    KeyCode::Left | KeyCode::Up
+                        if matches!(self.currently_editing, Some(CurrentlyEditing::Type)) =>
+                    {
+                        self.value_type = self.value_type.previous();
+                    }
+                    KeyCode::Right | KeyCode::Down
+                        if matches!(self.currently_editing, Some(CurrentlyEditing::Type)) =>
+                    {
+                        self.value_type = self.value_type.next();
+                    }
+                    _ => {
+                        if let Some(input) = self.focused_input() {
+                            input.handle_key(key);
                         }
+                    }
+                },
+            },
+            _ => {}
+        }
+        Ok(())
+    }
 
-                        _ => {}
-                    },
-                }
+    fn focused_input(&mut self) -> Option<&mut TextInput> {
+        match self.current_screen {
+            CurrentScreen::Searching => return Some(&mut self.search_input),
+            CurrentScreen::Querying => return Some(&mut self.query_input),
+            CurrentScreen::Exporting if self.export_field == ExportField::Destination => {
+                return Some(&mut self.export_input)
             }
+            CurrentScreen::Exporting => return None,
+            _ => {}
+        }
+        match self.currently_editing {
+            Some(CurrentlyEditing::Key) => Some(&mut self.key_input),
+            Some(CurrentlyEditing::Value) => Some(&mut self.value_input),
+            _ => None,
         }
-        Ok(())
     }
 
-    pub fn save_key_value(&mut self) {
-        self.pairs
-            .insert(self.key_input.clone(), self.value_input.clone());
-        self.key_input = String::new();
-        self.value_input = String::new();
+    /// Recompiles the search after the pattern changed and moves to the first match from the selection.
+    ///
+    /// An empty or invalid pattern shows the whole document again.
+    fn update_search(&mut self) {
+        self.search_error = None;
+        self.document_mut().filter = None;
+        if self.search_input.value().is_empty() {
+            return;
+        }
+        match Matcher::new(self.search_input.value(), self.search_regex) {
+            Ok(matcher) => self.document_mut().filter = Some(matcher),
+            Err(err) => {
+                self.search_error = Some(err.to_string());
+                return;
+            }
+        }
+
+        let rows = self.document().rows();
+        let selected = rows
+            .iter()
+            .find(|row| row.pointer == self.document().selected);
+        if !selected.is_some_and(|row| row.matched) {
+            self.document_mut().select_next_match();
+        }
+    }
+
+    fn clear_search(&mut self) {
+        self.search_input = TextInput::default();
+        self.search_error = None;
+        self.document_mut().filter = None;
+    }
+
+    /// Jumps to the single node the command bar picks, or projects the document onto several.
+    ///
+    /// Leaves the bar open with the reason when the query does not parse or picks nothing.
+    fn run_query(&mut self) {
+        let query = match Query::parse(self.query_input.value()) {
+            Ok(query) => query,
+            Err(err) => {
+                self.query_error = Some(err);
+                return;
+            }
+        };
+        let mut pointers = query.evaluate(self.document());
+        match pointers.len() {
+            0 => self.query_error = Some("no such node".to_string()),
+            1 => {
+                self.document_mut().reveal(pointers.remove(0));
+                self.clear_query();
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {
+                self.document_mut().selected = pointers[0].clone();
+                self.document_mut().projection = Some(pointers.into_iter().collect());
+                self.current_screen = CurrentScreen::Main;
+            }
+        }
+    }
+
+    fn clear_query(&mut self) {
+        self.query_input = TextInput::default();
+        self.query_error = None;
+        self.document_mut().projection = None;
+    }
+
+    /// Opens the editing popup to add a child to the selected node.
+    fn start_editing(&mut self) {
+        if let Some(target) = self.document().insert_target() {
+            self.edit_target = EditTarget::Insert(target);
+            self.key_input = TextInput::default();
+            self.value_input = TextInput::default();
+            self.value_type = ValueType::default();
+            self.open_popup();
+        }
+    }
+
+    /// Opens the editing popup pre-filled with the selected node.
+    fn start_updating(&mut self) {
+        let selected = self.document().selected.clone();
+        let Some(value) = self.document().get(&selected).cloned() else {
+            return;
+        };
+
+        self.value_type = ValueType::of(&value);
+        self.value_input = TextInput::from(value_text(&value));
+        self.key_input = TextInput::from(match selected.last() {
+            Some(Segment::Key(key)) => key.clone(),
+            _ => String::new(),
+        });
+        self.edit_target = EditTarget::Update(selected);
+        self.open_popup();
+    }
+
+    fn open_popup(&mut self) {
+        self.current_screen = CurrentScreen::Editing;
+        self.currently_editing = Some(if self.key_editable() {
+            CurrentlyEditing::Key
+        } else {
+            CurrentlyEditing::Value
+        });
+    }
+
+    /// Only object members have a key, array elements and the root do not.
+    fn key_editable(&self) -> bool {
+        match &self.edit_target {
+            EditTarget::Insert(parent) => {
+                matches!(self.document().get(parent), Some(Value::Object(_)))
+            }
+            EditTarget::Update(pointer) => matches!(pointer.last(), Some(Segment::Key(_))),
+        }
+    }
+
+    /// Rejects keys which would overwrite another member of the same object.
+    fn key_error(&self) -> Option<String> {
+        if !self.key_editable() {
+            return None;
+        }
+        let (parent, unchanged) = match &self.edit_target {
+            EditTarget::Insert(parent) => (parent.as_slice(), false),
+            EditTarget::Update(pointer) => (
+                &pointer[..pointer.len() - 1],
+                pointer.last() == Some(&Segment::Key(self.key_input.value().to_string())),
+            ),
+        };
+
+        (!unchanged && self.document().contains_key(parent, self.key_input.value()))
+            .then(|| format!("{:?} already exists", self.key_input.value()))
+    }
+
+    /// Writes the edited pair to the document.
+    ///
+    /// Returns `false` and keeps the popup open while the key collides or the value does not parse.
+    pub fn save_key_value(&mut self) -> bool {
+        if self.key_error().is_some() {
+            return false;
+        }
+        let Ok(value) = self.value_type.parse(self.value_input.value()) else {
+            return false;
+        };
+
+        let mut step = Vec::new();
+        match &self.edit_target {
+            EditTarget::Insert(parent) => {
+                if let Some(pointer) = self
+                    .document()
+                    .child_pointer(parent, self.key_input.value().to_string())
+                {
+                    step.push(Command::Insert {
+                        position: self.document().position(&pointer),
+                        pointer,
+                        value,
+                    });
+                }
+            }
+            EditTarget::Update(pointer) => {
+                if let Some(old) = self.document().get(pointer) {
+                    if *old != value {
+                        step.push(Command::Update {
+                            pointer: pointer.clone(),
+                            old: old.clone(),
+                            new: value,
+                        });
+                    }
+                }
+                let key = Segment::Key(self.key_input.value().to_string());
+                if self.key_editable() && pointer.last() != Some(&key) {
+                    let mut to = pointer.clone();
+                    to.pop();
+                    to.push(key);
+                    step.push(Command::Rename {
+                        from: pointer.clone(),
+                        to,
+                    });
+                }
+            }
+        }
+        self.document_mut().execute(step);
+        self.validate();
+
+        self.key_input = TextInput::default();
+        self.value_input = TextInput::default();
+        self.value_type = ValueType::default();
         self.currently_editing = None;
+        true
+    }
+
+    fn delete_selected(&mut self) {
+        let pointer = self.document().selected.clone();
+        if let Some(value) = self.document().get(&pointer).cloned() {
+            let position = self.document().position(&pointer);
+            self.document_mut().execute(vec![Command::Delete {
+                position,
+                pointer,
+                value,
+            }]);
+            self.validate();
+        }
     }
 
     pub fn toggle_editing(&mut self) {
         if let Some(edit_mode) = &self.currently_editing {
             match edit_mode {
-                CurrentlyEditing::Key => self.currently_editing = Some(CurrentlyEditing::Value),
+                CurrentlyEditing::Key => self.currently_editing = Some(CurrentlyEditing::Type),
+                CurrentlyEditing::Type => self.currently_editing = Some(CurrentlyEditing::Value),
+                CurrentlyEditing::Value if !self.key_editable() => {
+                    self.currently_editing = Some(CurrentlyEditing::Type)
+                }
                 CurrentlyEditing::Value => self.currently_editing = Some(CurrentlyEditing::Key),
             };
         } else {
@@ -149,32 +677,202 @@ impl App {
         }
     }
 
-    pub fn print_json(&self) -> serde_json::Result<()> {
-        let output = serde_json::to_string(&self.pairs)?;
-        println!("{}", output);
-        Ok(())
+    /// Moves to the next or previous tab, wrapping around.
+    ///
+    /// The search and command bar are left behind since they belong to the document they ran on.
+    fn switch_tab(&mut self, forward: bool) {
+        self.clear_search();
+        self.clear_query();
+        let count = self.documents.len();
+        self.current = if forward {
+            (self.current + 1) % count
+        } else {
+            (self.current + count - 1) % count
+        };
+        self.validate();
+    }
+
+    fn copy_selected(&mut self) {
+        let selected = &self.document().selected;
+        let key = match selected.last() {
+            Some(Segment::Key(key)) => Some(key.clone()),
+            _ => None,
+        };
+        if let Some(value) = self.document().get(selected).cloned() {
+            self.clipboard = Some(Clipboard { key, value });
+        }
+    }
+
+    /// Appends the copied node to the selected container, or to the parent of the selected leaf.
+    ///
+    /// Goes through the editing popup instead when an object member needs a new key,
+    /// because the copied one is taken or the node came from an array.
+    fn paste(&mut self) {
+        let Some(Clipboard { key, value }) = &self.clipboard else {
+            return;
+        };
+        let Some(parent) = self.document().insert_target() else {
+            return;
+        };
+        let key = key.clone().unwrap_or_default();
+        let value = value.clone();
+
+        let into_object = matches!(self.document().get(&parent), Some(Value::Object(_)));
+        if into_object && (key.is_empty() || self.document().contains_key(&parent, &key)) {
+            self.edit_target = EditTarget::Insert(parent);
+            self.key_input = TextInput::from(key);
+            self.value_type = ValueType::of(&value);
+            self.value_input = TextInput::from(value_text(&value));
+            self.open_popup();
+            return;
+        }
+        if let Some(pointer) = self.document().child_pointer(&parent, key) {
+            let position = self.document().position(&pointer);
+            self.document_mut().execute(vec![Command::Insert {
+                pointer,
+                position,
+                value,
+            }]);
+            self.validate();
+        }
+    }
+
+    /// Asks before quitting whenever something would otherwise be lost.
+    fn request_quit(&mut self) {
+        let saved = |document: &Document| document.path.is_some() && !document.dirty;
+        if self.documents.iter().all(saved) {
+            self.should_quit = true;
+        } else {
+            self.current_screen = CurrentScreen::Exiting;
+        }
+    }
+
+    /// Opens the export dialog, keeping the settings of the previous export.
+    fn start_exporting(&mut self, quit_after_export: bool) {
+        self.quit_after_export = quit_after_export;
+        self.export_field = ExportField::Format;
+        self.export_error = None;
+        self.current_screen = CurrentScreen::Exporting;
+    }
+
+    fn toggle_export_field(&mut self) {
+        self.export_field = match self.export_field {
+            ExportField::Format if self.export.format == Format::Json => ExportField::Indent,
+            ExportField::Format | ExportField::Indent => ExportField::Destination,
+            ExportField::Destination => ExportField::Format,
+        };
+    }
+
+    fn change_export_setting(&mut self, forward: bool) {
+        match self.export_field {
+            ExportField::Format if forward => self.export.format = self.export.format.next(),
+            ExportField::Format => self.export.format = self.export.format.previous(),
+            ExportField::Indent if forward => {
+                self.export.indent = (self.export.indent + 1).min(Export::MAX_INDENT)
+            }
+            ExportField::Indent => self.export.indent = self.export.indent.saturating_sub(1),
+            ExportField::Destination => {}
+        }
+    }
+
+    /// Writes a file export right away, stdout exports wait until the terminal is restored.
+    ///
+    /// Problems such as values TOML cannot hold keep the dialog open with the reason.
+    fn finish_exporting(&mut self) {
+        let value = self.document().sorted_value();
+        let destination = self.export_input.value().trim();
+        self.export.destination = (!destination.is_empty()).then(|| PathBuf::from(destination));
+
+        let result = match &self.export.destination {
+            Some(path) => self.export.write(&value, path),
+            None => self.export.render(&value).map(|_| {
+                self.output = Some(Output {
+                    export: self.export.clone(),
+                    document: self.current,
+                })
+            }),
+        };
+        match result {
+            Ok(()) if self.quit_after_export => self.should_quit = true,
+            Ok(()) => self.current_screen = CurrentScreen::Main,
+            Err(err) => self.export_error = Some(err.to_string()),
+        }
     }
 }
 
 impl Widget for &mut App {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let [title_block, containt, bottom_block] = Layout::vertical([
+        let tabs_height = if self.documents.len() > 1 { 1 } else { 0 };
+        let [tabs, title_block, containt, bottom_block] = Layout::vertical([
+            Constraint::Length(tabs_height),
             Constraint::Length(3),
             Constraint::Min(1),
             Constraint::Length(3),
         ])
         .areas(area);
 
+        self.render_tabs(tabs, buf);
         App::render_title(title_block, buf);
-        self.render_containt(containt, buf);
+        let containt = if self.show_violations {
+            let [containt, violations] =
+                Layout::horizontal([Constraint::Min(1), Constraint::Percentage(40)])
+                    .areas(containt);
+            self.render_violations(violations, buf);
+            containt
+        } else {
+            containt
+        };
+        if self.current_screen == CurrentScreen::Diffing {
+            self.render_diff(containt, buf);
+        } else if self.current_screen == CurrentScreen::Searching
+            || self.document().filter.is_some()
+        {
+            let [containt, search_bar] =
+                Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(containt);
+            self.render_containt(containt, buf);
+            self.render_search_bar(search_bar, buf);
+        } else if self.current_screen == CurrentScreen::Querying
+            || self.document().projection.is_some()
+        {
+            let [containt, query_bar] =
+                Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(containt);
+            self.render_containt(containt, buf);
+            self.render_query_bar(query_bar, buf);
+        } else {
+            self.render_containt(containt, buf);
+        }
         self.render_bottom(bottom_block, buf);
 
         self.render_popup_editing(area, buf);
+        self.render_popup_delete(area, buf);
         self.render_popup_exit(area, buf);
+        self.render_popup_export(area, buf);
+        self.render_popup_error(area, buf);
     }
 }
 
 impl App {
+    /// One tab per open document, marked with `*` while it has unsaved changes.
+    fn render_tabs(&self, area: Rect, buf: &mut Buffer) {
+        let titles = self.documents.iter().map(|document| {
+            let name = match &document.path {
+                Some(path) => path
+                    .file_name()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy(),
+                None if self.pipeline => "stdin".into(),
+                None => "untitled".into(),
+            };
+            let modified = if document.dirty { " *" } else { "" };
+            format!("{name}{modified}")
+        });
+
+        Tabs::new(titles)
+            .select(self.current)
+            .highlight_style(Style::default().fg(Color::Black).bg(Color::Green))
+            .render(area, buf);
+    }
+
     fn render_title(area: Rect, buf: &mut Buffer) {
         let title_block = Block::default()
             .borders(Borders::ALL)
@@ -189,17 +887,228 @@ impl App {
         title.render(area, buf);
     }
 
-    fn render_containt(&self, area: Rect, buf: &mut Buffer) {
-        let mut list_items = Vec::<ListItem>::new();
+    fn render_containt(&mut self, area: Rect, buf: &mut Buffer) {
+        let rows = self.document().rows();
+        let list_items: Vec<ListItem> = rows
+            .iter()
+            .map(|row| ListItem::new(self.row_line(row)))
+            .collect();
 
-        for key in self.pairs.keys() {
-            list_items.push(ListItem::new(Line::from(Span::styled(
-                format!("{: <25} : {}", key, self.pairs.get(key).unwrap()),
-                Style::default().fg(Color::Yellow),
-            ))));
-        }
+        let selected = self.document().selected_row(&rows);
+        self.document_mut().list_state.select(Some(selected));
+
+        let list = List::new(list_items).highlight_style(Style::default().bg(Color::DarkGray));
+        StatefulWidget::render(list, area, buf, &mut self.document_mut().list_state);
+    }
+
+    /// The `/` prompt below the tree, with the search mode and how many nodes match.
+    fn render_search_bar(&self, area: Rect, buf: &mut Buffer) {
+        let mode = if self.search_regex {
+            "regex"
+        } else {
+            "substring"
+        };
+        let status = match &self.search_error {
+            Some(err) => Span::styled(format!(" {mode}: {err}"), Style::default().fg(Color::Red)),
+            None => {
+                let matches = match self.document().filter {
+                    Some(_) => self
+                        .document()
+                        .rows()
+                        .iter()
+                        .filter(|row| row.matched)
+                        .count(),
+                    None => 0,
+                };
+                Span::styled(
+                    format!(" {mode}, {matches} found"),
+                    Style::default().fg(Color::DarkGray),
+                )
+            }
+        };
+        let focused = self.current_screen == CurrentScreen::Searching;
+        let status_width = status.width() as u16;
+        let [input_area, status_area] =
+            Layout::horizontal([Constraint::Min(1), Constraint::Length(status_width)]).areas(area);
+
+        let mut input = self
+            .search_input
+            .line(input_area.width.saturating_sub(1), focused);
+        input.spans.insert(0, Span::raw("/"));
+        input.render(input_area, buf);
+        status.render(status_area, buf);
+    }
+
+    /// The last loaded or saved document next to the current one, line by line.
+    fn render_diff(&mut self, area: Rect, buf: &mut Buffer) {
+        // Borrows the field rather than going through `document()` so the scroll can be clamped.
+        let document = &self.documents[self.current];
+        let rows = diff(&document.saved, &document.value);
+        let last_row = rows.len().saturating_sub(1);
+        self.diff_scroll = self.diff_scroll.min(last_row as u16);
+
+        let line = |row: &DiffRow, value: Option<&Value>| {
+            let Some(value) = value else {
+                return Line::default();
+            };
+            let (marker, color) = match row.change {
+                Change::Unchanged => (' ', Color::Gray),
+                Change::Inner => (' ', Color::White),
+                Change::Added => ('+', Color::Green),
+                Change::Removed => ('-', Color::Red),
+                Change::Modified => ('~', Color::Yellow),
+            };
+            Line::styled(
+                format!(
+                    "{marker} {}{} : {}",
+                    "  ".repeat(row.depth),
+                    row.label,
+                    value_span(value).content
+                ),
+                Style::default().fg(color),
+            )
+        };
+        let old_lines: Vec<Line> = rows.iter().map(|row| line(row, row.old)).collect();
+        let new_lines: Vec<Line> = rows.iter().map(|row| line(row, row.new)).collect();
+
+        let new_title = match changes(&rows) {
+            0 => "Current, no changes".to_string(),
+            1 => "Current, 1 change".to_string(),
+            count => format!("Current, {count} changes"),
+        };
+        let [old_area, new_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(area);
+        Paragraph::new(old_lines)
+            .scroll((self.diff_scroll, 0))
+            .block(Block::default().title("Last saved").borders(Borders::ALL))
+            .render(old_area, buf);
+        Paragraph::new(new_lines)
+            .scroll((self.diff_scroll, 0))
+            .block(Block::default().title(new_title).borders(Borders::ALL))
+            .render(new_area, buf);
+    }
 
-        List::new(list_items).render(area, buf);
+    /// The `:` prompt below the tree, with how many nodes the projection shows.
+    fn render_query_bar(&self, area: Rect, buf: &mut Buffer) {
+        let status = match (&self.query_error, &self.document().projection) {
+            (Some(err), _) => Span::styled(format!(" {err}"), Style::default().fg(Color::Red)),
+            (None, Some(projection)) => Span::styled(
+                format!(" {} nodes", projection.len()),
+                Style::default().fg(Color::DarkGray),
+            ),
+            (None, None) => Span::styled(
+                " /pointer/0 or .path[].key",
+                Style::default().fg(Color::DarkGray),
+            ),
+        };
+        let focused = self.current_screen == CurrentScreen::Querying;
+        let status_width = status.width() as u16;
+        let [input_area, status_area] =
+            Layout::horizontal([Constraint::Min(1), Constraint::Length(status_width)]).areas(area);
+
+        let mut input = self
+            .query_input
+            .line(input_area.width.saturating_sub(1), focused);
+        input.spans.insert(0, Span::raw(":"));
+        input.render(input_area, buf);
+        status.render(status_area, buf);
+    }
+
+    /// The side panel listing where the document breaks the schema, titled after the schema file.
+    fn render_violations(&self, area: Rect, buf: &mut Buffer) {
+        let name = self
+            .schema
+            .as_ref()
+            .and_then(|schema| schema.path.file_name())
+            .map_or("Schema".into(), |name| name.to_string_lossy());
+        let (title, border) = match self.violations.len() {
+            0 => (format!("{name}: valid"), Color::Green),
+            count => (format!("{name}: {count} violations"), Color::Red),
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border));
+
+        let lines: Vec<Line> = self
+            .violations
+            .iter()
+            .flat_map(|violation| {
+                let path = if violation.path.is_empty() {
+                    "root"
+                } else {
+                    violation.path.as_str()
+                };
+                [
+                    Line::styled(path, Style::default().fg(Color::Yellow)),
+                    Line::styled(
+                        format!("  {}", violation.message),
+                        Style::default().fg(Color::Red),
+                    ),
+                ]
+            })
+            .collect();
+
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(area, buf);
+    }
+
+    fn row_line(&self, row: &Row) -> Line<'static> {
+        let value = self.document().get(&row.pointer).unwrap_or(&Value::Null);
+        let folded = self.document().filter.is_none() && self.document().is_collapsed(&row.pointer);
+        let marker = match value {
+            Value::Object(_) | Value::Array(_) if folded => "▸ ",
+            Value::Object(_) | Value::Array(_) => "▾ ",
+            _ => "  ",
+        };
+        let label = match row.pointer.last() {
+            None => "root".to_string(),
+            Some(Segment::Key(key)) => key.clone(),
+            Some(Segment::Index(index)) => format!("[{index}]"),
+        };
+
+        let filter = self.document().filter.as_ref();
+        let mut spans = vec![Span::raw(format!("{}{}", "  ".repeat(row.depth), marker))];
+        let label_ranges = match (filter, row.pointer.last()) {
+            (Some(matcher), Some(Segment::Key(key))) => matcher.ranges(key),
+            _ => Vec::new(),
+        };
+        spans.extend(highlight(
+            label,
+            &label_ranges,
+            Style::default().fg(Color::Yellow),
+        ));
+        spans.push(Span::styled(" : ", Style::default().fg(Color::White)));
+
+        let value_span = value_span(value);
+        match (filter, scalar_text(value)) {
+            (Some(matcher), Some(text)) => {
+                // Strings are shown with their quotes, which the search does not see.
+                let offset = usize::from(value.is_string());
+                let ranges: Vec<_> = matcher
+                    .ranges(&text)
+                    .into_iter()
+                    .map(|range| range.start + offset..range.end + offset)
+                    .collect();
+                spans.extend(highlight(
+                    value_span.content.into_owned(),
+                    &ranges,
+                    value_span.style,
+                ));
+            }
+            _ => spans.push(value_span),
+        }
+        if self
+            .violations
+            .iter()
+            .any(|violation| violation.pointer.as_ref() == Some(&row.pointer))
+        {
+            spans.push(Span::styled(" ✗", Style::default().fg(Color::Red)));
+        }
+        Line::from(spans)
     }
 
     fn render_bottom(&self, area: Rect, buf: &mut Buffer) {
@@ -211,7 +1120,22 @@ impl App {
                 CurrentScreen::Editing => {
                     Span::styled("Editing Mode", Style::default().fg(Color::Yellow))
                 }
+                CurrentScreen::Deleting => {
+                    Span::styled("Deleting", Style::default().fg(Color::Red))
+                }
                 CurrentScreen::Exiting => Span::styled("Exiting", Style::default().fg(Color::Red)),
+                CurrentScreen::Searching => {
+                    Span::styled("Search Mode", Style::default().fg(Color::Cyan))
+                }
+                CurrentScreen::Querying => {
+                    Span::styled("Command Mode", Style::default().fg(Color::Cyan))
+                }
+                CurrentScreen::Diffing => {
+                    Span::styled("Reviewing Changes", Style::default().fg(Color::Cyan))
+                }
+                CurrentScreen::Exporting => {
+                    Span::styled("Exporting", Style::default().fg(Color::Cyan))
+                }
             }
             .to_owned(),
             Span::styled(" | ", Style::default().fg(Color::White)),
@@ -221,6 +1145,9 @@ impl App {
                         CurrentlyEditing::Key => {
                             Span::styled("Editing Json Key", Style::default().fg(Color::Green))
                         }
+                        CurrentlyEditing::Type => {
+                            Span::styled("Choosing Json Type", Style::default().fg(Color::Cyan))
+                        }
                         CurrentlyEditing::Value => Span::styled(
                             "Editing Json Value",
                             Style::default().fg(Color::LightGreen),
@@ -230,23 +1157,91 @@ impl App {
                     Span::styled("Not Editing Anything", Style::default().fg(Color::DarkGray))
                 }
             },
+            Span::styled(" | ", Style::default().fg(Color::White)),
+            if self.document().dirty {
+                Span::styled("Modified", Style::default().fg(Color::Yellow))
+            } else {
+                Span::styled("Saved", Style::default().fg(Color::DarkGray))
+            },
+            Span::styled(" | ", Style::default().fg(Color::White)),
+            Span::styled(
+                format!("{} order", self.document().sort_mode),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(" | ", Style::default().fg(Color::White)),
+            Span::styled(
+                format!(
+                    "{} undo / {} redo",
+                    self.document().history.undo_depth(),
+                    self.document().history.redo_depth()
+                ),
+                Style::default().fg(Color::DarkGray),
+            ),
         ];
 
-        let mode_footer = Paragraph::new(Line::from(current_navigation_text))
-            .block(Block::default().borders(Borders::ALL));
+        let mode_line = Line::from(current_navigation_text);
+        let mode_width = mode_line.width() as u16 + 2;
+        let mode_footer = Paragraph::new(mode_line).block(Block::default().borders(Borders::ALL));
 
         let current_keys_hint = {
             match self.current_screen {
-                CurrentScreen::Main => Span::styled(
-                    "(q) to quit / (e) to make new pair",
+                CurrentScreen::Main => {
+                    let mut hints = vec![
+                        "(q) quit",
+                        "(e) add",
+                        "(enter) edit",
+                        "(d) delete",
+                        "(u) undo",
+                        "(^r) redo",
+                        "(space) fold",
+                        "(o) order",
+                        "(/) search",
+                        "(:) go to path",
+                        "(n/N) next/previous match",
+                        "(x) export",
+                        "(c) changes",
+                        "(y) copy",
+                    ];
+                    if self.clipboard.is_some() {
+                        hints.push("(p) paste");
+                    }
+                    if self.documents.len() > 1 {
+                        hints.push("(^PgUp/^PgDn) switch tab");
+                    }
+                    if self.schema.is_some() {
+                        hints.push("(v) violations");
+                    }
+                    if self.document().path.is_some() {
+                        hints.push("(s) save");
+                    }
+                    Span::styled(hints.join(" / "), Style::default().fg(Color::Red))
+                }
+                CurrentScreen::Editing => Span::styled(
+                    "(ESC) to cancel / (TAB) to switch boxes / (←→) to change type / enter to complete",
                     Style::default().fg(Color::Red),
                 ),
-                CurrentScreen::Editing => Span::styled(
-                    "(ESC) to cancel / (TAB) to switch boxes/enter to complete",
+                CurrentScreen::Deleting => Span::styled(
+                    "(y) to delete / (n) to keep",
                     Style::default().fg(Color::Red),
                 ),
                 CurrentScreen::Exiting => Span::styled(
-                    "(q) to quit / (e) to make new pair",
+                    "(y) yes / (n) no / (x) export / (ESC) to cancel",
+                    Style::default().fg(Color::Red),
+                ),
+                CurrentScreen::Diffing => Span::styled(
+                    "(ESC) to go back / (↑↓) to scroll / (PgUp/PgDn) to scroll by 10",
+                    Style::default().fg(Color::Red),
+                ),
+                CurrentScreen::Querying => Span::styled(
+                    "(ESC) to cancel / enter to jump to one node or show all it picks",
+                    Style::default().fg(Color::Red),
+                ),
+                CurrentScreen::Exporting => Span::styled(
+                    "(ESC) to cancel / (TAB) to switch fields / (←→) to change / enter to export",
+                    Style::default().fg(Color::Red),
+                ),
+                CurrentScreen::Searching => Span::styled(
+                    "(ESC) to clear / (TAB) to toggle regex / enter to confirm",
                     Style::default().fg(Color::Red),
                 ),
             }
@@ -257,7 +1252,7 @@ impl App {
 
         let footer_chunks = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .constraints([Constraint::Length(mode_width), Constraint::Min(0)])
             .split(area);
 
         mode_footer.render(footer_chunks[0], buf);
@@ -266,35 +1261,102 @@ impl App {
 
     fn render_popup_editing(&self, area: Rect, buf: &mut Buffer) {
         if let Some(editing) = &self.currently_editing {
+            let title = match self.edit_target {
+                EditTarget::Insert(_) => "Enter a new child",
+                EditTarget::Update(_) => "Edit the selected node",
+            };
             let popup_block = Block::default()
-                .title("Enter a new key-value pair")
+                .title(title)
                 .borders(Borders::NONE)
                 .style(Style::default().bg(Color::DarkGray));
 
             let area = centered_rect(60, 25, area);
+            Clear.render(area, buf);
             popup_block.render(area, buf);
 
             let popup_chunks = Layout::default()
                 .direction(Direction::Horizontal)
                 .margin(1)
-                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .constraints([
+                    Constraint::Percentage(40),
+                    Constraint::Length(12),
+                    Constraint::Min(0),
+                ])
                 .split(area);
 
-            let mut key_block = Block::default().title("Key").borders(Borders::ALL);
+            let key_title = if self.key_editable() {
+                "Key"
+            } else {
+                "Key (none)"
+            };
+            let mut key_block = Block::default().title(key_title).borders(Borders::ALL);
+            let mut type_block = Block::default().title("Type").borders(Borders::ALL);
             let mut value_block = Block::default().title("Value").borders(Borders::ALL);
 
             let active_style = Style::default().bg(Color::LightYellow).fg(Color::Black);
 
             match &editing {
                 CurrentlyEditing::Key => key_block = key_block.style(active_style),
+                CurrentlyEditing::Type => type_block = type_block.style(active_style),
                 CurrentlyEditing::Value => value_block = value_block.style(active_style),
             }
 
-            let key_text = Paragraph::new(self.key_input.clone()).block(key_block);
+            let key_focused = matches!(editing, CurrentlyEditing::Key);
+            let key_width = popup_chunks[0].width.saturating_sub(2);
+            let mut key_lines = vec![self.key_input.line(key_width, key_focused)];
+            if let Some(err) = self.key_error() {
+                key_block = key_block.border_style(Style::default().fg(Color::Red));
+                key_lines.push(Line::styled(err, Style::default().fg(Color::Red)));
+            }
+            let key_text = Paragraph::new(key_lines)
+                .wrap(Wrap { trim: false })
+                .block(key_block);
             key_text.render(popup_chunks[0], buf);
 
-            let value_text = Paragraph::new(self.value_input.clone()).block(value_block);
-            value_text.render(popup_chunks[1], buf);
+            let type_text = Paragraph::new(format!("< {} >", self.value_type))
+                .centered()
+                .block(type_block);
+            type_text.render(popup_chunks[1], buf);
+
+            let value_focused = matches!(editing, CurrentlyEditing::Value);
+            let value_width = popup_chunks[2].width.saturating_sub(2);
+            let mut value_lines = vec![self.value_input.line(value_width, value_focused)];
+            if let Err(err) = self.value_type.parse(self.value_input.value()) {
+                value_block = value_block.border_style(Style::default().fg(Color::Red));
+                value_lines.push(Line::styled(err, Style::default().fg(Color::Red)));
+            }
+            let value_text = Paragraph::new(value_lines)
+                .wrap(Wrap { trim: false })
+                .block(value_block);
+            value_text.render(popup_chunks[2], buf);
+        }
+    }
+
+    fn render_popup_delete(&self, area: Rect, buf: &mut Buffer) {
+        if let CurrentScreen::Deleting = self.current_screen {
+            let label = match self.document().selected.last() {
+                Some(Segment::Key(key)) => format!("{key:?}"),
+                Some(Segment::Index(index)) => format!("element [{index}]"),
+                None => "the root".to_string(),
+            };
+
+            let popup_block = Block::default()
+                .title("Y/N")
+                .borders(Borders::NONE)
+                .style(Style::default().bg(Color::DarkGray));
+
+            let delete_text = Text::styled(
+                format!("Would you like to delete {label} and everything below it? (y/n)"),
+                Style::default().fg(Color::Red),
+            );
+
+            let delete_paragraph = Paragraph::new(delete_text)
+                .block(popup_block)
+                .wrap(Wrap { trim: false });
+
+            let area = centered_rect(60, 25, area);
+            Clear.render(area, buf);
+            delete_paragraph.render(area, buf);
         }
     }
 
@@ -307,10 +1369,25 @@ impl App {
                 .borders(Borders::NONE)
                 .style(Style::default().bg(Color::DarkGray));
 
-            let exit_text = Text::styled(
-                "Would you like to output the buffer as json? (y/n)",
-                Style::default().fg(Color::Red),
-            );
+            let unsaved: Vec<String> = self
+                .documents
+                .iter()
+                .filter(|document| document.dirty)
+                .filter_map(|document| document.path.as_ref())
+                .map(|path| path.display().to_string())
+                .collect();
+            let question = match &self.document().path {
+                Some(_) => format!(
+                    "Would you like to save your changes to {} before quitting? (y/n)",
+                    unsaved.join(", ")
+                ),
+                None if self.pipeline => {
+                    "Would you like to write the document to stdout? (y) yes / (n) abort"
+                        .to_string()
+                }
+                None => "Would you like to output the buffer as json? (y/n)".to_string(),
+            };
+            let exit_text = Text::styled(question, Style::default().fg(Color::Red));
 
             let exit_paragraph = Paragraph::new(exit_text)
                 .block(popup_block)
@@ -320,6 +1397,101 @@ impl App {
             exit_paragraph.render(area, buf);
         }
     }
+
+    fn render_popup_export(&self, area: Rect, buf: &mut Buffer) {
+        if let CurrentScreen::Exporting = self.current_screen {
+            let popup_block = Block::default()
+                .title("Export the document")
+                .borders(Borders::NONE)
+                .style(Style::default().bg(Color::DarkGray));
+
+            let area = centered_rect(60, 60, area);
+            Clear.render(area, buf);
+            popup_block.render(area, buf);
+
+            let [format_area, indent_area, destination_area, error_area] = Layout::vertical([
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Min(0),
+            ])
+            .margin(1)
+            .areas(area);
+
+            let active_style = Style::default().bg(Color::LightYellow).fg(Color::Black);
+            let field_block = |title, field| {
+                let block = Block::default().title(title).borders(Borders::ALL);
+                if self.export_field == field {
+                    block.style(active_style)
+                } else {
+                    block
+                }
+            };
+
+            Paragraph::new(format!("< {} >", self.export.format))
+                .block(field_block("Format", ExportField::Format))
+                .render(format_area, buf);
+
+            let indent = if self.export.format == Format::Json {
+                format!("< {} spaces >", self.export.indent)
+            } else {
+                "only used by json".to_string()
+            };
+            Paragraph::new(indent)
+                .block(field_block("Indent", ExportField::Indent))
+                .render(indent_area, buf);
+
+            let focused = self.export_field == ExportField::Destination;
+            let destination = if self.export_input.value().is_empty() && !focused {
+                Line::styled(
+                    "stdout, once the editor quits",
+                    Style::default().fg(Color::Gray),
+                )
+            } else {
+                self.export_input
+                    .line(destination_area.width.saturating_sub(2), focused)
+            };
+            Paragraph::new(destination)
+                .block(field_block(
+                    "File (empty for stdout)",
+                    ExportField::Destination,
+                ))
+                .render(destination_area, buf);
+
+            if let Some(err) = &self.export_error {
+                Paragraph::new(Line::styled(err.as_str(), Style::default().fg(Color::Red)))
+                    .wrap(Wrap { trim: false })
+                    .render(error_area, buf);
+            }
+        }
+    }
+
+    fn render_popup_error(&self, area: Rect, buf: &mut Buffer) {
+        if let Some(err) = &self.error {
+            let popup_block = Block::default()
+                .title("Error")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .style(Style::default().bg(Color::DarkGray));
+
+            let error_text = Text::from(vec![
+                Line::styled(err.to_string(), Style::default().fg(Color::Red)),
+                Line::default(),
+                Line::styled(
+                    "Press any key to continue",
+                    Style::default().fg(Color::Gray),
+                ),
+            ]);
+
+            let error_paragraph = Paragraph::new(error_text)
+                .block(popup_block)
+                .wrap(Wrap { trim: false });
+
+            let area = centered_rect(60, 25, area);
+            Clear.render(area, buf);
+            error_paragraph.render(area, buf);
+        }
+    }
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -341,3 +1513,47 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+fn value_span(value: &Value) -> Span<'static> {
+    match value {
+        Value::Object(map) => Span::styled(
+            format!("{{{}}}", map.len()),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Value::Array(items) => Span::styled(
+            format!("[{}]", items.len()),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Value::String(_) => Span::styled(value.to_string(), Style::default().fg(Color::Green)),
+        Value::Number(_) => Span::styled(value.to_string(), Style::default().fg(Color::Cyan)),
+        Value::Bool(_) => Span::styled(value.to_string(), Style::default().fg(Color::Magenta)),
+        Value::Null => Span::styled(value.to_string(), Style::default().fg(Color::DarkGray)),
+    }
+}
+
+/// Splits `text` into spans, painting the byte `ranges` over `style`.
+fn highlight(text: String, ranges: &[Range<usize>], style: Style) -> Vec<Span<'static>> {
+    let match_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for range in ranges {
+        if range.start > end {
+            spans.push(Span::styled(text[end..range.start].to_string(), style));
+        }
+        spans.push(Span::styled(text[range.clone()].to_string(), match_style));
+        end = range.end;
+    }
+    if end < text.len() || spans.is_empty() {
+        spans.push(Span::styled(text[end..].to_string(), style));
+    }
+    spans
+}
+
+/// The text a value is edited as, strings without quotes and null as nothing.
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
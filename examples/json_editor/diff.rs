@@ -0,0 +1,81 @@
+use serde_json::Value;
+
+/// How one line of the diff differs between the saved and the current document.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Unchanged,
+    Added,
+    Removed,
+    /// A scalar with another value, or a node which changed type.
+    Modified,
+    /// A container whose children changed, listed on the following lines.
+    Inner,
+}
+
+/// One line shown in both columns of the diff.
+pub struct DiffRow<'a> {
+    pub depth: usize,
+    /// The key or `[index]` of the node, `root` for the top.
+    pub label: String,
+    pub old: Option<&'a Value>,
+    pub new: Option<&'a Value>,
+    pub change: Change,
+}
+
+/// Lines comparing `old` with `new`, unchanged containers folded into a single line.
+///
+/// Object members are matched by key, removed ones staying where they were,
+/// while array elements are compared by position.
+pub fn diff<'a>(old: &'a Value, new: &'a Value) -> Vec<DiffRow<'a>> {
+    let mut rows = Vec::new();
+    push_rows("root".to_string(), 0, Some(old), Some(new), &mut rows);
+    rows
+}
+
+pub fn changes(rows: &[DiffRow]) -> usize {
+    rows.iter()
+        .filter(|row| !matches!(row.change, Change::Unchanged | Change::Inner))
+        .count()
+}
+
+fn push_rows<'a>(
+    label: String,
+    depth: usize,
+    old: Option<&'a Value>,
+    new: Option<&'a Value>,
+    rows: &mut Vec<DiffRow<'a>>,
+) {
+    let change = match (old, new) {
+        (Some(old), Some(new)) if old == new => Change::Unchanged,
+        (Some(Value::Object(_)), Some(Value::Object(_)))
+        | (Some(Value::Array(_)), Some(Value::Array(_))) => Change::Inner,
+        (Some(_), Some(_)) => Change::Modified,
+        (None, _) => Change::Added,
+        (_, None) => Change::Removed,
+    };
+    rows.push(DiffRow {
+        depth,
+        label,
+        old,
+        new,
+        change,
+    });
+
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) if change == Change::Inner => {
+            for (key, old_child) in old {
+                push_rows(key.clone(), depth + 1, Some(old_child), new.get(key), rows);
+            }
+            for (key, new_child) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                push_rows(key.clone(), depth + 1, None, Some(new_child), rows);
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) if change == Change::Inner => {
+            for index in 0..old.len().max(new.len()) {
+                let label = format!("[{index}]");
+                push_rows(label, depth + 1, old.get(index), new.get(index), rows);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,614 @@
+use std::{
+    collections::HashSet,
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use ratatui::widgets::ListState;
+use serde_json::{Map, Value};
+
+use crate::{
+    error::{Error, Result},
+    history::{Command, History},
+    search::{scalar_text, Matcher},
+    sort_mode::SortMode,
+};
+
+/// A single step from a json value into one of its children.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Location of a value inside the document, the root being the empty pointer.
+pub type Pointer = Vec<Segment>;
+
+/// One visible line of the tree view.
+pub struct Row {
+    pub pointer: Pointer,
+    pub depth: usize,
+    /// Whether the row itself matches the search, rather than being shown for context.
+    pub matched: bool,
+}
+
+/// A json value tree together with the file it was loaded from and its view state.
+pub struct Document {
+    pub value: Value,
+    /// The document as last loaded or saved, which the diff view compares against.
+    pub saved: Value,
+    pub path: Option<PathBuf>,
+    pub dirty: bool,
+    pub selected: Pointer,
+    pub list_state: ListState,
+    pub history: History,
+    pub sort_mode: SortMode,
+    /// Restricts the rows to search matches and their ancestors, ignoring folds.
+    pub filter: Option<Matcher>,
+    /// Restricts the rows to the nodes picked by a path query, with their ancestors and contents.
+    pub projection: Option<HashSet<Pointer>>,
+    collapsed: HashSet<Pointer>,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self::new(Value::Object(Map::new()), None)
+    }
+}
+
+impl Document {
+    pub fn new(value: Value, path: Option<PathBuf>) -> Self {
+        Self {
+            saved: value.clone(),
+            value,
+            path,
+            dirty: false,
+            selected: Pointer::new(),
+            list_state: ListState::default(),
+            history: History::default(),
+            sort_mode: SortMode::default(),
+            filter: None,
+            projection: None,
+            collapsed: HashSet::new(),
+        }
+    }
+
+    /// Opens the json document stored at `path`.
+    ///
+    /// A missing file starts an empty object which is created on the first save.
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let value = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(|source| Error::Parse {
+                origin: path.display().to_string(),
+                source,
+            })?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Value::Object(Map::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Self::new(value, Some(path)))
+    }
+
+    /// Reads a json document with no file behind it, such as one piped in on stdin.
+    ///
+    /// Empty input starts an empty object.
+    pub fn read(mut reader: impl Read, origin: &str) -> Result<Self> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        let value = if contents.trim().is_empty() {
+            Value::Object(Map::new())
+        } else {
+            serde_json::from_str(&contents).map_err(|source| Error::Parse {
+                origin: origin.to_string(),
+                source,
+            })?
+        };
+
+        Ok(Self::new(value, None))
+    }
+
+    /// The document with its objects in the current sort order, as it gets written out.
+    pub fn sorted_value(&self) -> Value {
+        self.sort_mode.apply(&self.value)
+    }
+
+    /// Writes the document back to the opened file.
+    pub fn save(&mut self) -> Result<()> {
+        if let Some(path) = &self.path {
+            let mut contents = serde_json::to_string_pretty(&self.sorted_value())?;
+            contents.push('\n');
+            write_atomic(path, contents.as_bytes()).map_err(|source| Error::Write {
+                path: path.clone(),
+                source,
+            })?;
+            self.saved = self.value.clone();
+            self.dirty = false;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, pointer: &[Segment]) -> Option<&Value> {
+        pointer
+            .iter()
+            .try_fold(&self.value, |value, segment| match (value, segment) {
+                (Value::Object(map), Segment::Key(key)) => map.get(key),
+                (Value::Array(items), Segment::Index(index)) => items.get(*index),
+                _ => None,
+            })
+    }
+
+    /// Looks up a JSON pointer such as `/servers/0/port`, the empty string being the root.
+    pub fn resolve(&self, path: &str) -> Option<Pointer> {
+        if path.is_empty() {
+            return Some(Pointer::new());
+        }
+        let mut pointer = Pointer::new();
+        let mut value = &self.value;
+        for token in path.strip_prefix('/')?.split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            let (segment, child) = match value {
+                Value::Object(map) => (Segment::Key(token.clone()), map.get(&token)?),
+                Value::Array(items) => {
+                    let index = token.parse().ok()?;
+                    (Segment::Index(index), items.get(index)?)
+                }
+                _ => return None,
+            };
+            pointer.push(segment);
+            value = child;
+        }
+        Some(pointer)
+    }
+
+    pub fn get_mut(&mut self, pointer: &[Segment]) -> Option<&mut Value> {
+        pointer
+            .iter()
+            .try_fold(&mut self.value, |value, segment| match (value, segment) {
+                (Value::Object(map), Segment::Key(key)) => map.get_mut(key),
+                (Value::Array(items), Segment::Index(index)) => items.get_mut(*index),
+                _ => None,
+            })
+    }
+
+    /// Flattens the expanded part of the tree into the lines shown on screen.
+    pub fn rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        match (&self.filter, &self.projection) {
+            (Some(matcher), _) => {
+                self.push_matching_rows(&self.value, Pointer::new(), matcher, &mut rows);
+            }
+            (None, Some(projection)) => {
+                self.push_projected_rows(&self.value, Pointer::new(), projection, &mut rows);
+            }
+            (None, None) => self.push_rows(&self.value, Pointer::new(), &mut rows),
+        }
+        rows
+    }
+
+    fn push_rows(&self, value: &Value, pointer: Pointer, rows: &mut Vec<Row>) {
+        let expanded = !self.collapsed.contains(&pointer);
+        rows.push(Row {
+            depth: pointer.len(),
+            pointer: pointer.clone(),
+            matched: false,
+        });
+        if !expanded {
+            return;
+        }
+
+        for (segment, child) in self.children(value) {
+            let mut child_pointer = pointer.clone();
+            child_pointer.push(segment);
+            self.push_rows(child, child_pointer, rows);
+        }
+    }
+
+    /// Pushes the rows of the projected nodes, the ancestors leading to them and what they contain.
+    fn push_projected_rows(
+        &self,
+        value: &Value,
+        pointer: Pointer,
+        projection: &HashSet<Pointer>,
+        rows: &mut Vec<Row>,
+    ) {
+        if projection.contains(&pointer) {
+            let index = rows.len();
+            self.push_rows(value, pointer, rows);
+            rows[index].matched = true;
+            return;
+        }
+        if !projection.iter().any(|picked| picked.starts_with(&pointer)) {
+            return;
+        }
+
+        rows.push(Row {
+            depth: pointer.len(),
+            pointer: pointer.clone(),
+            matched: false,
+        });
+        for (segment, child) in self.children(value) {
+            let mut child_pointer = pointer.clone();
+            child_pointer.push(segment);
+            self.push_projected_rows(child, child_pointer, projection, rows);
+        }
+    }
+
+    /// Pushes the rows of the nodes matching the key or scalar value, along with their ancestors.
+    ///
+    /// Returns whether anything at or below `pointer` matched.
+    fn push_matching_rows(
+        &self,
+        value: &Value,
+        pointer: Pointer,
+        matcher: &Matcher,
+        rows: &mut Vec<Row>,
+    ) -> bool {
+        let key_matched =
+            matches!(pointer.last(), Some(Segment::Key(key)) if matcher.is_match(key));
+        let matched = key_matched || scalar_text(value).is_some_and(|text| matcher.is_match(&text));
+        let index = rows.len();
+        rows.push(Row {
+            depth: pointer.len(),
+            pointer: pointer.clone(),
+            matched,
+        });
+
+        let mut found = matched;
+        for (segment, child) in self.children(value) {
+            let mut child_pointer = pointer.clone();
+            child_pointer.push(segment);
+            found |= self.push_matching_rows(child, child_pointer, matcher, rows);
+        }
+        if !found {
+            rows.truncate(index);
+        }
+        found
+    }
+
+    /// The direct children of a container in the current sort order, leaves have none.
+    fn children<'a>(&self, value: &'a Value) -> Vec<(Segment, &'a Value)> {
+        match value {
+            Value::Object(map) => self
+                .sort_mode
+                .members(map)
+                .into_iter()
+                .map(|(key, child)| (Segment::Key(key.clone()), child))
+                .collect(),
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(index, child)| (Segment::Index(index), child))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn is_collapsed(&self, pointer: &[Segment]) -> bool {
+        self.collapsed.contains(pointer)
+    }
+
+    pub fn toggle_collapsed(&mut self) {
+        if !self.collapsed.remove(&self.selected) && self.selected_is_container() {
+            self.collapsed.insert(self.selected.clone());
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        self.select_offset(1);
+    }
+
+    pub fn select_previous(&mut self) {
+        self.select_offset(-1);
+    }
+
+    fn select_offset(&mut self, offset: isize) {
+        let rows = self.rows();
+        let current = self.selected_row(&rows);
+        let index = current
+            .saturating_add_signed(offset)
+            .min(rows.len().saturating_sub(1));
+        if let Some(row) = rows.into_iter().nth(index) {
+            self.selected = row.pointer;
+        }
+    }
+
+    /// Selects `pointer`, unfolding its ancestors so it shows up.
+    pub fn reveal(&mut self, pointer: Pointer) {
+        for len in 0..pointer.len() {
+            self.collapsed.remove(&pointer[..len]);
+        }
+        self.selected = pointer;
+    }
+
+    /// Moves to the next search match after the selection, wrapping around at the end.
+    pub fn select_next_match(&mut self) {
+        let rows = self.rows();
+        let current = self.selected_row(&rows);
+        let next = (1..=rows.len())
+            .map(|offset| (current + offset) % rows.len())
+            .find(|index| rows[*index].matched);
+        if let Some(index) = next {
+            self.selected = rows[index].pointer.clone();
+        }
+    }
+
+    /// Moves to the previous search match before the selection, wrapping around at the start.
+    pub fn select_previous_match(&mut self) {
+        let rows = self.rows();
+        let current = self.selected_row(&rows);
+        let previous = (1..=rows.len())
+            .map(|offset| (current + rows.len() - offset) % rows.len())
+            .find(|index| rows[*index].matched);
+        if let Some(index) = previous {
+            self.selected = rows[index].pointer.clone();
+        }
+    }
+
+    /// Collapses the selected node, or moves to its parent when there is nothing to collapse.
+    pub fn collapse_or_select_parent(&mut self) {
+        if self.selected_is_container() && !self.is_collapsed(&self.selected) {
+            self.collapsed.insert(self.selected.clone());
+        } else {
+            self.selected.pop();
+        }
+    }
+
+    /// Expands the selected node, or moves to its first child when it is already open.
+    pub fn expand_or_select_child(&mut self) {
+        if self.collapsed.remove(&self.selected) {
+            return;
+        }
+        let first_child = self
+            .get(&self.selected)
+            .and_then(|value| self.children(value).into_iter().next())
+            .map(|(segment, _)| segment);
+        if let Some(segment) = first_child {
+            self.selected.push(segment);
+        }
+    }
+
+    /// Index of the selected node among `rows`, falling back to the top when it is hidden.
+    pub fn selected_row(&self, rows: &[Row]) -> usize {
+        rows.iter()
+            .position(|row| row.pointer == self.selected)
+            .unwrap_or_default()
+    }
+
+    fn selected_is_container(&self) -> bool {
+        matches!(
+            self.get(&self.selected),
+            Some(Value::Object(_) | Value::Array(_))
+        )
+    }
+
+    /// The container a new child is added to: the selected node itself, or its parent for leaves.
+    pub fn insert_target(&self) -> Option<Pointer> {
+        if self.selected_is_container() {
+            return Some(self.selected.clone());
+        }
+        let (_, parent) = self.selected.split_last()?;
+        Some(parent.to_vec())
+    }
+
+    /// Where a child named `key` would be added under the container at `parent`.
+    ///
+    /// `key` is ignored when the container is an array, the child is appended instead.
+    pub fn child_pointer(&self, parent: &[Segment], key: String) -> Option<Pointer> {
+        let segment = match self.get(parent)? {
+            Value::Object(_) => Segment::Key(key),
+            Value::Array(items) => Segment::Index(items.len()),
+            _ => return None,
+        };
+        let mut pointer = parent.to_vec();
+        pointer.push(segment);
+        Some(pointer)
+    }
+
+    /// Index of the node at `pointer` among its siblings, or the end of its parent
+    /// when there is no such node yet.
+    pub fn position(&self, pointer: &[Segment]) -> usize {
+        let Some((last, parent)) = pointer.split_last() else {
+            return 0;
+        };
+        match (self.get(parent), last) {
+            (Some(Value::Object(map)), Segment::Key(key)) => {
+                map.keys().position(|k| k == key).unwrap_or(map.len())
+            }
+            (Some(Value::Array(items)), Segment::Index(index)) => (*index).min(items.len()),
+            _ => 0,
+        }
+    }
+
+    pub fn contains_key(&self, parent: &[Segment], key: &str) -> bool {
+        matches!(self.get(parent), Some(Value::Object(map)) if map.contains_key(key))
+    }
+
+    /// Applies the commands of one user action and records them for undo.
+    pub fn execute(&mut self, step: Vec<Command>) {
+        for command in &step {
+            self.apply(command);
+        }
+        self.history.push(step);
+        self.update_dirty();
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(step) = self.history.undo() {
+            step.iter().for_each(|command| self.apply(command));
+            self.update_dirty();
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(step) = self.history.redo() {
+            step.iter().for_each(|command| self.apply(command));
+            self.update_dirty();
+        }
+    }
+
+    /// Undoing back to the saved document makes it clean again.
+    fn update_dirty(&mut self) {
+        self.dirty = self.value != self.saved;
+    }
+
+    /// Performs a single command and selects the node it touched.
+    fn apply(&mut self, command: &Command) {
+        match command {
+            Command::Insert {
+                pointer,
+                position,
+                value,
+            } => self.insert(pointer, *position, value.clone()),
+            Command::Update { pointer, new, .. } => self.replace(pointer, new.clone()),
+            Command::Delete { pointer, .. } => self.remove(pointer),
+            Command::Rename { from, to } => self.rename(from, to),
+        }
+    }
+
+    fn insert(&mut self, pointer: &[Segment], position: usize, value: Value) {
+        let Some((last, parent)) = pointer.split_last() else {
+            return;
+        };
+        match (self.get_mut(parent), last) {
+            (Some(Value::Object(map)), Segment::Key(key)) => {
+                map.shift_insert(position.min(map.len()), key.clone(), value);
+            }
+            (Some(Value::Array(items)), Segment::Index(_)) => {
+                items.insert(position.min(items.len()), value);
+                self.forget_collapsed(parent);
+            }
+            _ => return,
+        }
+
+        self.collapsed.remove(parent);
+        self.selected = pointer.to_vec();
+    }
+
+    fn replace(&mut self, pointer: &[Segment], value: Value) {
+        if let Some(slot) = self.get_mut(pointer) {
+            *slot = value;
+            self.forget_collapsed(pointer);
+            self.selected = pointer.to_vec();
+        }
+    }
+
+    /// Moves the object member at `from` to `to`, both sharing the same parent.
+    fn rename(&mut self, from: &[Segment], to: &[Segment]) {
+        let (Some((Segment::Key(old_key), parent)), Some(Segment::Key(new_key))) =
+            (from.split_last(), to.last())
+        else {
+            return;
+        };
+        let Some(Value::Object(map)) = self.get_mut(parent) else {
+            return;
+        };
+        let Some(position) = map.keys().position(|key| key == old_key) else {
+            return;
+        };
+        let Some(value) = map.shift_remove(old_key) else {
+            return;
+        };
+        map.shift_insert(position, new_key.clone(), value);
+
+        self.forget_collapsed(from);
+        self.selected = to.to_vec();
+    }
+
+    /// Removes the node at `pointer` and selects its parent.
+    fn remove(&mut self, pointer: &[Segment]) {
+        let Some((last, parent)) = pointer.split_last() else {
+            return;
+        };
+        match (self.get_mut(parent), last) {
+            (Some(Value::Object(map)), Segment::Key(key)) => {
+                map.shift_remove(key);
+            }
+            (Some(Value::Array(items)), Segment::Index(index)) if *index < items.len() => {
+                items.remove(*index);
+            }
+            _ => return,
+        }
+
+        // Array elements after the removed one shift down, so their fold state is stale too.
+        match last {
+            Segment::Key(_) => self.forget_collapsed(pointer),
+            Segment::Index(_) => self.forget_collapsed(parent),
+        }
+        self.selected = parent.to_vec();
+    }
+
+    /// Drops the fold state of `pointer` and everything below it after the nodes moved.
+    fn forget_collapsed(&mut self, pointer: &[Segment]) {
+        self.collapsed
+            .retain(|collapsed| !collapsed.starts_with(pointer));
+    }
+}
+
+/// Writes `contents` next to `path` first and renames it into place,
+/// so an interrupted save never leaves a truncated file behind.
+///
+/// An existing file keeps its permissions, and a symlink is written through rather than replaced.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let (path, permissions) = match fs::canonicalize(path) {
+        Ok(target) => {
+            let permissions = fs::metadata(&target)?.permissions();
+            (target, Some(permissions))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => (path.to_path_buf(), None),
+        Err(err) => return Err(err),
+    };
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path does not name a file"))?;
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = File::create(&tmp_path).and_then(|mut file| {
+        if let Some(permissions) = permissions {
+            file.set_permissions(permissions)?;
+        }
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    match result.and_then(|_| fs::rename(&tmp_path, &path)) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn write_atomic_keeps_links_and_permissions() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = std::env::temp_dir().join(format!("json_editor-{}-write", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("secrets.json");
+        let link = dir.join("link.json");
+        fs::write(&target, "{}").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        symlink(&target, &link).unwrap();
+
+        let written = write_atomic(&link, b"{\"a\": 1}");
+        let link_kept = fs::symlink_metadata(&link).map(|meta| meta.file_type().is_symlink());
+        let mode = fs::metadata(&target).map(|meta| meta.permissions().mode() & 0o777);
+        let contents = fs::read_to_string(&target);
+        fs::remove_dir_all(&dir).unwrap();
+
+        written.unwrap();
+        assert!(link_kept.unwrap());
+        assert_eq!(mode.unwrap(), 0o600);
+        assert_eq!(contents.unwrap(), "{\"a\": 1}");
+    }
+}
@@ -0,0 +1,30 @@
+use std::{io, path::PathBuf};
+
+use thiserror::Error;
+
+/// Everything that can go wrong while loading, editing and writing out documents.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("IO error: {0}")]
+    IO(#[from] io::Error),
+    /// Input which is not json, `origin` naming the file or stream it was read from.
+    #[error("Could not parse {origin}: {source}")]
+    Parse {
+        origin: String,
+        source: serde_json::Error,
+    },
+    #[error("Could not write {}: {source}", path.display())]
+    Write { path: PathBuf, source: io::Error },
+    #[error("Schema error: {0}")]
+    Schema(String),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("YAML error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("TOML error: {0}")]
+    Toml(#[from] toml::ser::Error),
+    #[error("Export error: {0}")]
+    Export(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
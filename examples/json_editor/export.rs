@@ -0,0 +1,124 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::{ser::PrettyFormatter, Value};
+use strum::{Display, EnumCount, FromRepr};
+
+use crate::{
+    document::write_atomic,
+    error::{Error, Result},
+};
+
+/// The formats a document can be written out as.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Display, FromRepr, EnumCount)]
+pub enum Format {
+    #[default]
+    #[strum(to_string = "json")]
+    Json,
+    #[strum(to_string = "compact json")]
+    CompactJson,
+    #[strum(to_string = "yaml")]
+    Yaml,
+    #[strum(to_string = "toml")]
+    Toml,
+}
+
+impl Format {
+    pub fn previous(self) -> Self {
+        let previous_index = (self as usize + Self::COUNT - 1) % Self::COUNT;
+        Self::from_repr(previous_index).unwrap_or(self)
+    }
+
+    pub fn next(self) -> Self {
+        let next_index = (self as usize + 1) % Self::COUNT;
+        Self::from_repr(next_index).unwrap_or(self)
+    }
+}
+
+/// How and where to write the document.
+#[derive(Clone)]
+pub struct Export {
+    pub format: Format,
+    /// Spaces per level, only used by pretty json.
+    pub indent: usize,
+    /// The file to write, stdout when there is none.
+    pub destination: Option<PathBuf>,
+}
+
+impl Default for Export {
+    fn default() -> Self {
+        Self {
+            format: Format::default(),
+            indent: 2,
+            destination: None,
+        }
+    }
+}
+
+impl Export {
+    pub const MAX_INDENT: usize = 8;
+
+    /// The document as text in the chosen format, ending with a newline.
+    pub fn render(&self, value: &Value) -> Result<String> {
+        let mut output = match self.format {
+            Format::Json => {
+                let indent = " ".repeat(self.indent);
+                let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+                let mut bytes = Vec::new();
+                let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, formatter);
+                value.serialize(&mut serializer)?;
+                String::from_utf8(bytes).expect("serde_json writes utf-8")
+            }
+            Format::CompactJson => serde_json::to_string(value)?,
+            Format::Yaml => serde_yaml::to_string(value)?,
+            Format::Toml => {
+                let mut unsupported = Vec::new();
+                toml_unsupported(value, String::new(), &mut unsupported);
+                if !unsupported.is_empty() {
+                    return Err(Error::Export(format!(
+                        "TOML cannot represent {}",
+                        unsupported.join(", ")
+                    )));
+                }
+                toml::to_string_pretty(value)?
+            }
+        };
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        Ok(output)
+    }
+
+    /// Writes the document to `path`, stdout being left to the caller once the terminal is restored.
+    pub fn write(&self, value: &Value, path: &Path) -> Result<()> {
+        let output = self.render(value)?;
+        write_atomic(path, output.as_bytes()).map_err(|source| Error::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// Collects the places in `value` which have no TOML equivalent.
+///
+/// TOML documents are tables at the top level and have no null.
+fn toml_unsupported(value: &Value, path: String, unsupported: &mut Vec<String>) {
+    match value {
+        Value::Null => unsupported.push(format!("null at {path}")),
+        Value::Object(map) => {
+            for (key, child) in map {
+                let key = key.replace('~', "~0").replace('/', "~1");
+                toml_unsupported(child, format!("{path}/{key}"), unsupported);
+            }
+        }
+        Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                toml_unsupported(child, format!("{path}/{index}"), unsupported);
+            }
+        }
+        _ => {}
+    }
+    if path.is_empty() && !value.is_object() {
+        unsupported.push("a top level value other than an object".to_string());
+    }
+}
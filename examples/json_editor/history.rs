@@ -0,0 +1,268 @@
+use serde_json::Value;
+
+use crate::document::Pointer;
+
+/// A reversible change to a document.
+#[derive(Clone, Debug)]
+pub enum Command {
+    /// Adds `value` at `pointer`, shifting the siblings from `position` on.
+    Insert {
+        pointer: Pointer,
+        position: usize,
+        value: Value,
+    },
+    /// Replaces the node at `pointer`.
+    Update {
+        pointer: Pointer,
+        old: Value,
+        new: Value,
+    },
+    /// Removes the node at `pointer`, keeping it and its `position` so it can be restored.
+    Delete {
+        pointer: Pointer,
+        position: usize,
+        value: Value,
+    },
+    /// Moves an object member from one key to another.
+    Rename { from: Pointer, to: Pointer },
+}
+
+impl Command {
+    /// The command which undoes this one.
+    pub fn inverse(&self) -> Self {
+        match self.clone() {
+            Self::Insert {
+                pointer,
+                position,
+                value,
+            } => Self::Delete {
+                pointer,
+                position,
+                value,
+            },
+            Self::Update { pointer, old, new } => Self::Update {
+                pointer,
+                old: new,
+                new: old,
+            },
+            Self::Delete {
+                pointer,
+                position,
+                value,
+            } => Self::Insert {
+                pointer,
+                position,
+                value,
+            },
+            Self::Rename { from, to } => Self::Rename { from: to, to: from },
+        }
+    }
+}
+
+/// Undo and redo stacks, each step holding the commands of one user action.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Vec<Command>>,
+    redo: Vec<Vec<Command>>,
+}
+
+impl History {
+    /// Records a new step, which makes everything undone so far unreachable.
+    pub fn push(&mut self, step: Vec<Command>) {
+        if !step.is_empty() {
+            self.undo.push(step);
+            self.redo.clear();
+        }
+    }
+
+    /// Takes the latest step and returns the commands reverting it, in order.
+    pub fn undo(&mut self) -> Option<Vec<Command>> {
+        let step = self.undo.pop()?;
+        let inverse = step.iter().rev().map(Command::inverse).collect();
+        self.redo.push(step);
+        Some(inverse)
+    }
+
+    /// Takes the latest undone step and returns its commands again.
+    pub fn redo(&mut self) -> Option<Vec<Command>> {
+        let step = self.redo.pop()?;
+        self.undo.push(step.clone());
+        Some(step)
+    }
+
+    pub fn undo_depth(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_depth(&self) -> usize {
+        self.redo.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::document::{Document, Segment};
+
+    fn key(key: &str) -> Segment {
+        Segment::Key(key.to_string())
+    }
+
+    fn document() -> Document {
+        Document::new(json!({"a": 1, "b": [10, 20, 30], "c": {"d": true}}), None)
+    }
+
+    /// Executes `step`, then checks that undoing it restores the document, key order included,
+    /// and leaves it clean, and that redoing it gives the same result again.
+    fn assert_round_trip(step: Vec<Command>, expected: Value) {
+        let mut document = document();
+        let original = document.value.clone();
+        document.execute(step);
+        assert_eq!(document.value, expected);
+        assert!(document.dirty);
+        document.undo();
+        assert_eq!(document.value, original);
+        assert!(!document.dirty);
+        assert!(document
+            .value
+            .as_object()
+            .unwrap()
+            .keys()
+            .eq(original.as_object().unwrap().keys()));
+        document.redo();
+        assert_eq!(document.value, expected);
+        assert!(document.dirty);
+    }
+
+    #[test]
+    fn inverse_swaps_insert_and_delete() {
+        let insert = Command::Insert {
+            pointer: vec![key("x")],
+            position: 1,
+            value: json!(null),
+        };
+        assert!(matches!(
+            insert.inverse(),
+            Command::Delete { pointer, position: 1, value: Value::Null } if pointer == [key("x")]
+        ));
+        assert!(matches!(
+            insert.inverse().inverse(),
+            Command::Insert { position: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn inverse_swaps_update_values_and_rename_keys() {
+        let update = Command::Update {
+            pointer: vec![key("a")],
+            old: json!(1),
+            new: json!(2),
+        };
+        assert!(
+            matches!(update.inverse(), Command::Update { old, new, .. } if old == 2 && new == 1)
+        );
+        let rename = Command::Rename {
+            from: vec![key("a")],
+            to: vec![key("z")],
+        };
+        assert!(matches!(
+            rename.inverse(),
+            Command::Rename { from, to } if from == [key("z")] && to == [key("a")]
+        ));
+    }
+
+    #[test]
+    fn undoes_an_object_insert() {
+        assert_round_trip(
+            vec![Command::Insert {
+                pointer: vec![key("x")],
+                position: 1,
+                value: json!("new"),
+            }],
+            json!({"a": 1, "x": "new", "b": [10, 20, 30], "c": {"d": true}}),
+        );
+    }
+
+    #[test]
+    fn undoes_an_array_delete_in_place() {
+        assert_round_trip(
+            vec![Command::Delete {
+                pointer: vec![key("b"), Segment::Index(1)],
+                position: 1,
+                value: json!(20),
+            }],
+            json!({"a": 1, "b": [10, 30], "c": {"d": true}}),
+        );
+    }
+
+    #[test]
+    fn undoes_a_member_delete_at_its_position() {
+        assert_round_trip(
+            vec![Command::Delete {
+                pointer: vec![key("a")],
+                position: 0,
+                value: json!(1),
+            }],
+            json!({"b": [10, 20, 30], "c": {"d": true}}),
+        );
+    }
+
+    #[test]
+    fn undoes_an_update_and_a_rename() {
+        assert_round_trip(
+            vec![Command::Update {
+                pointer: vec![key("c"), key("d")],
+                old: json!(true),
+                new: json!(false),
+            }],
+            json!({"a": 1, "b": [10, 20, 30], "c": {"d": false}}),
+        );
+        assert_round_trip(
+            vec![Command::Rename {
+                from: vec![key("b")],
+                to: vec![key("z")],
+            }],
+            json!({"a": 1, "z": [10, 20, 30], "c": {"d": true}}),
+        );
+    }
+
+    #[test]
+    fn undoes_a_step_in_reverse_order() {
+        // Renaming a member and then updating it under the new key only undoes cleanly backwards.
+        assert_round_trip(
+            vec![
+                Command::Rename {
+                    from: vec![key("a")],
+                    to: vec![key("z")],
+                },
+                Command::Update {
+                    pointer: vec![key("z")],
+                    old: json!(1),
+                    new: json!(2),
+                },
+            ],
+            json!({"z": 2, "b": [10, 20, 30], "c": {"d": true}}),
+        );
+    }
+
+    #[test]
+    fn a_new_step_drops_the_redo_stack() {
+        let mut history = History::default();
+        let step = || {
+            vec![Command::Rename {
+                from: vec![key("a")],
+                to: vec![key("z")],
+            }]
+        };
+        history.push(step());
+        history.push(Vec::new());
+        assert_eq!((history.undo_depth(), history.redo_depth()), (1, 0));
+        history.undo();
+        assert_eq!((history.undo_depth(), history.redo_depth()), (0, 1));
+        history.push(step());
+        assert_eq!((history.undo_depth(), history.redo_depth()), (1, 0));
+        assert!(history.redo().is_none());
+    }
+}
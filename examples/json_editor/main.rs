@@ -1,35 +1,131 @@
-use crossterm::event::DisableMouseCapture;
+use std::{
+    fs::OpenOptions,
+    io::{self, IsTerminal, Write},
+    panic,
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use argh::FromArgs;
+use crossterm::event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste};
 use ratatui::{
     crossterm::{
+        cursor::Show,
         event::EnableMouseCapture,
         execute,
         terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     },
-    prelude::{Backend, CrosstermBackend},
+    prelude::CrosstermBackend,
     Terminal,
 };
-use std::io;
 
 use app::App;
+use error::Result;
 
 mod app;
+mod diff;
+mod document;
+mod error;
+mod export;
+mod history;
+mod query;
+mod schema;
+mod search;
+mod sort_mode;
+mod text_input;
+mod value_type;
+
+/// Json Editor
+#[derive(Debug, FromArgs)]
+struct Cli {
+    /// json files to edit, each in its own tab and created on save if it does not exist yet.
+    /// Without any, json piped in on stdin is edited and written back to stdout.
+    #[argh(positional)]
+    paths: Vec<PathBuf>,
+
+    /// json schema to validate the document against.
+    #[argh(option, short = 's')]
+    schema: Option<PathBuf>,
+}
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("json_editor: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<ExitCode> {
+    let cli: Cli = argh::from_env();
+    let mut app = if !cli.paths.is_empty() {
+        App::open(cli.paths)?
+    } else if !io::stdin().is_terminal() {
+        App::read_stdin()?
+    } else {
+        App::default()
+    };
+    if let Some(schema) = cli.schema {
+        app.load_schema(schema)?;
+    }
+
+    install_panic_hook();
     enable_raw_mode()?;
-    let mut stderr = io::stderr();
-    execute!(stderr, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stderr);
+    let mut output = ui_output()?;
+    execute!(
+        output,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
+    let backend = CrosstermBackend::new(output);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::default().run(&mut terminal);
+    let result = app.run(&mut terminal);
+
+    restore_terminal()?;
+    result?;
+    match &app.output {
+        Some(output) => io::stdout().write_all(output.render(&app.documents)?.as_bytes())?,
+        // Downstream commands in a pipeline should not run on an aborted edit.
+        None if app.pipeline => return Ok(ExitCode::FAILURE),
+        None => {}
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
 
+/// Puts the terminal back the way the shell expects it.
+fn restore_terminal() -> io::Result<()> {
     disable_raw_mode()?;
     execute!(
-        terminal.backend_mut(),
+        ui_output()?,
         LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        Show
+    )
+}
+
+/// Restores the terminal before the panic message is printed,
+/// which would otherwise be lost on the alternate screen.
+fn install_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        hook(info);
+    }));
+}
 
-    Ok(())
+/// Where the interface is drawn, stderr unless it is redirected, then the terminal itself.
+///
+/// Stdout is left alone so the edited document can be piped on.
+fn ui_output() -> io::Result<Box<dyn Write>> {
+    if io::stderr().is_terminal() {
+        Ok(Box::new(io::stderr()))
+    } else {
+        Ok(Box::new(OpenOptions::new().write(true).open("/dev/tty")?))
+    }
 }
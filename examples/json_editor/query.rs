@@ -0,0 +1,278 @@
+use serde_json::Value;
+
+use crate::document::{Document, Pointer, Segment};
+
+/// What can be typed in the `:` command bar.
+pub enum Query {
+    /// A JSON pointer such as `/servers/0/port`.
+    Pointer(String),
+    /// A jq-like path such as `.servers[].port`.
+    Path(Vec<Step>),
+}
+
+/// One step of a jq-like path.
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    /// `.name` or `["name"]`, a member of an object.
+    Key(String),
+    /// `[2]`, an array element, counted from the end when negative.
+    Index(isize),
+    /// `[]`, every member or element.
+    Each,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if input.is_empty() || input.starts_with('/') {
+            return Ok(Self::Pointer(input.to_string()));
+        }
+        if !input.starts_with('.') {
+            return Err("expected a JSON pointer starting with / or a path starting with .".into());
+        }
+        parse_path(input).map(Self::Path)
+    }
+
+    /// The nodes of `document` picked by the query.
+    pub fn evaluate(&self, document: &Document) -> Vec<Pointer> {
+        match self {
+            Self::Pointer(path) => document.resolve(path).into_iter().collect(),
+            Self::Path(steps) => steps.iter().fold(vec![Pointer::new()], |pointers, step| {
+                pointers
+                    .into_iter()
+                    .flat_map(|pointer| {
+                        let value = document.get(&pointer).unwrap_or(&Value::Null);
+                        step.segments(value).into_iter().map(move |segment| {
+                            let mut child = pointer.clone();
+                            child.push(segment);
+                            child
+                        })
+                    })
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl Step {
+    /// The children of `value` this step goes to, nothing when they do not exist.
+    fn segments(&self, value: &Value) -> Vec<Segment> {
+        match (self, value) {
+            (Self::Key(key), Value::Object(map)) if map.contains_key(key) => {
+                vec![Segment::Key(key.clone())]
+            }
+            (Self::Index(index), Value::Array(items)) => {
+                let index = if *index < 0 {
+                    items.len().checked_sub(index.unsigned_abs())
+                } else {
+                    Some(index.unsigned_abs())
+                };
+                index
+                    .filter(|index| *index < items.len())
+                    .map(Segment::Index)
+                    .into_iter()
+                    .collect()
+            }
+            (Self::Each, Value::Object(map)) => map.keys().cloned().map(Segment::Key).collect(),
+            (Self::Each, Value::Array(items)) => (0..items.len()).map(Segment::Index).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn parse_path(input: &str) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let after = after.trim_start();
+            let (step, after) = if after.starts_with('"') {
+                let (key, after) = parse_string(after)?;
+                (Step::Key(key), after)
+            } else {
+                let end = after.find(']').unwrap_or(after.len());
+                (parse_index(after[..end].trim())?, &after[end..])
+            };
+            steps.push(step);
+            rest = after
+                .trim_start()
+                .strip_prefix(']')
+                .ok_or_else(|| "missing ] after [".to_string())?;
+        } else if let Some(after) = rest.strip_prefix('.') {
+            if after.starts_with('"') {
+                let (key, after) = parse_string(after)?;
+                steps.push(Step::Key(key));
+                rest = after;
+            } else {
+                let end = after
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                    .unwrap_or(after.len());
+                if end > 0 {
+                    steps.push(Step::Key(after[..end].to_string()));
+                } else if let Some(unexpected) = after.chars().next().filter(|c| *c != '[') {
+                    return Err(format!("unexpected {unexpected:?} after ."));
+                }
+                rest = &after[end..];
+            }
+        } else {
+            let unexpected = rest.chars().next().unwrap_or_default();
+            return Err(format!("unexpected {unexpected:?}, expected . or ["));
+        }
+    }
+    Ok(steps)
+}
+
+/// The step written between square brackets when it is not a quoted key.
+fn parse_index(inside: &str) -> Result<Step, String> {
+    if inside.is_empty() {
+        return Ok(Step::Each);
+    }
+    inside
+        .parse()
+        .map(Step::Index)
+        .map_err(|_| format!("{inside:?} is not an index"))
+}
+
+/// Reads the json string literal at the start of `input`, returning it and what follows.
+fn parse_string(input: &str) -> Result<(String, &str), String> {
+    let mut escaped = false;
+    let end = input
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| {
+            let closing = *c == '"' && !escaped;
+            escaped = *c == '\\' && !escaped;
+            closing
+        })
+        .map(|(index, _)| index)
+        .ok_or_else(|| "missing closing quote".to_string())?;
+    let key = serde_json::from_str(&input[..=end]).map_err(|err| err.to_string())?;
+    Ok((key, &input[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn key(key: &str) -> Step {
+        Step::Key(key.to_string())
+    }
+
+    #[test]
+    fn parses_keys_indexes_and_each() {
+        assert_eq!(
+            parse_path(".servers[].port").unwrap(),
+            [key("servers"), Step::Each, key("port")]
+        );
+        assert_eq!(
+            parse_path(".a[2][ -1 ]").unwrap(),
+            [key("a"), Step::Index(2), Step::Index(-1)]
+        );
+        assert_eq!(
+            parse_path(".snake_case.kebab-case").unwrap(),
+            [key("snake_case"), key("kebab-case")]
+        );
+        assert_eq!(parse_path(".").unwrap(), []);
+    }
+
+    #[test]
+    fn parses_quoted_keys() {
+        assert_eq!(parse_path(r#"."a b""#).unwrap(), [key("a b")]);
+        assert_eq!(parse_path(r#".["a\"b"]"#).unwrap(), [key("a\"b")]);
+        assert_eq!(parse_path(r#".["a\\"].b"#).unwrap(), [key("a\\"), key("b")]);
+        assert_eq!(parse_path(r#".[ "]" ]"#).unwrap(), [key("]")]);
+    }
+
+    #[test]
+    fn a_dot_may_be_followed_by_a_bracket() {
+        assert_eq!(parse_path(".[0]").unwrap(), [Step::Index(0)]);
+        assert_eq!(parse_path(".a.[]").unwrap(), [key("a"), Step::Each]);
+        assert_eq!(
+            parse_path(".a[0].b").unwrap(),
+            [key("a"), Step::Index(0), key("b")]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        assert_eq!(parse_path(".a[0").unwrap_err(), "missing ] after [");
+        assert_eq!(parse_path(".[x]").unwrap_err(), r#""x" is not an index"#);
+        assert_eq!(parse_path(r#".["a"#).unwrap_err(), "missing closing quote");
+        assert_eq!(parse_path("..a").unwrap_err(), "unexpected '.' after .");
+        assert_eq!(
+            parse_path(".a!").unwrap_err(),
+            "unexpected '!', expected . or ["
+        );
+        assert!(parse_path(r#".["\x"]"#).is_err());
+    }
+
+    #[test]
+    fn parse_string_returns_the_rest() {
+        assert_eq!(
+            parse_string(r#""a\"b"]"#).unwrap(),
+            ("a\"b".to_string(), "]")
+        );
+        assert_eq!(parse_string(r#""""#).unwrap(), (String::new(), ""));
+        assert!(parse_string(r#""a\""#).is_err());
+    }
+
+    #[test]
+    fn tells_pointers_from_paths() {
+        assert!(matches!(Query::parse(" /a/0 "), Ok(Query::Pointer(path)) if path == "/a/0"));
+        assert!(matches!(Query::parse(""), Ok(Query::Pointer(path)) if path.is_empty()));
+        assert!(matches!(Query::parse(".a"), Ok(Query::Path(steps)) if steps == [key("a")]));
+        assert!(Query::parse("a").is_err());
+    }
+
+    #[test]
+    fn negative_indexes_count_from_the_end() {
+        let items = json!([10, 20, 30]);
+        assert_eq!(Step::Index(-1).segments(&items), [Segment::Index(2)]);
+        assert_eq!(Step::Index(-3).segments(&items), [Segment::Index(0)]);
+        assert_eq!(Step::Index(-4).segments(&items), []);
+        assert_eq!(Step::Index(2).segments(&items), [Segment::Index(2)]);
+        assert_eq!(Step::Index(3).segments(&items), []);
+        assert_eq!(Step::Index(0).segments(&json!([])), []);
+    }
+
+    #[test]
+    fn steps_skip_what_does_not_exist() {
+        let object = json!({"b": 1, "a": 2});
+        assert_eq!(key("a").segments(&object), [Segment::Key("a".into())]);
+        assert_eq!(key("c").segments(&object), []);
+        assert_eq!(Step::Index(0).segments(&object), []);
+        assert_eq!(key("0").segments(&json!([1])), []);
+        assert_eq!(
+            Step::Each.segments(&object),
+            [Segment::Key("b".into()), Segment::Key("a".into())]
+        );
+        assert_eq!(Step::Each.segments(&json!(1)), []);
+    }
+
+    #[test]
+    fn evaluates_a_path_over_every_branch() {
+        let document = Document::new(
+            json!({"servers": [{"port": 80}, {"host": "b"}, {"port": 443}]}),
+            None,
+        );
+        let query = Query::parse(".servers[].port").unwrap();
+        let port = |index| {
+            vec![
+                Segment::Key("servers".into()),
+                Segment::Index(index),
+                Segment::Key("port".into()),
+            ]
+        };
+        assert_eq!(query.evaluate(&document), [port(0), port(2)]);
+        assert_eq!(
+            Query::parse(".").unwrap().evaluate(&document),
+            [Pointer::new()]
+        );
+        assert_eq!(
+            Query::parse("/servers/9").unwrap().evaluate(&document),
+            Vec::<Pointer>::new()
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use std::{fs, path::PathBuf};
+
+use jsonschema::Validator;
+use serde_json::Value;
+
+use crate::{
+    document::{Document, Pointer},
+    error::{Error, Result},
+};
+
+/// A JSON Schema the document is checked against after every change.
+pub struct Schema {
+    /// The schema file, named in the title of the violations panel.
+    pub path: PathBuf,
+    validator: Validator,
+}
+
+/// One place where the document does not conform to the schema.
+pub struct Violation {
+    /// Where the offending node sits, as a JSON pointer.
+    pub path: String,
+    /// The offending node in the tree, `None` when the path no longer resolves.
+    pub pointer: Option<Pointer>,
+    pub message: String,
+}
+
+impl Schema {
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let schema: Value =
+            serde_json::from_str(&fs::read_to_string(&path)?).map_err(|source| Error::Parse {
+                origin: path.display().to_string(),
+                source,
+            })?;
+        let validator =
+            jsonschema::validator_for(&schema).map_err(|err| Error::Schema(err.to_string()))?;
+        Ok(Self { path, validator })
+    }
+
+    pub fn validate(&self, document: &Document) -> Vec<Violation> {
+        self.validator
+            .iter_errors(&document.value)
+            .map(|err| {
+                let path = err.instance_path.to_string();
+                Violation {
+                    pointer: document.resolve(&path),
+                    message: err.to_string(),
+                    path,
+                }
+            })
+            .collect()
+    }
+}
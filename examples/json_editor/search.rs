@@ -0,0 +1,51 @@
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+
+/// A compiled `/` search, either a plain substring or a regular expression.
+pub struct Matcher {
+    regex: Regex,
+}
+
+impl Matcher {
+    /// Compiles `pattern`, which is matched literally unless `regex` is set.
+    ///
+    /// Lowercase patterns ignore case, typing any uppercase letter makes the search case sensitive.
+    pub fn new(pattern: &str, regex: bool) -> Result<Self, regex::Error> {
+        let source = if regex {
+            pattern.to_string()
+        } else {
+            regex::escape(pattern)
+        };
+        let regex = RegexBuilder::new(&source)
+            .case_insensitive(!pattern.chars().any(char::is_uppercase))
+            .build()?;
+        Ok(Self { regex })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// Byte ranges of the non-empty matches in `text`.
+    pub fn ranges(&self, text: &str) -> Vec<Range<usize>> {
+        self.regex
+            .find_iter(text)
+            .filter(|found| !found.is_empty())
+            .map(|found| found.range())
+            .collect()
+    }
+}
+
+/// The text a scalar is shown and searched as, strings without their quotes.
+pub fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Object(_) | Value::Array(_) => None,
+        Value::String(_) => {
+            let quoted = value.to_string();
+            Some(quoted[1..quoted.len() - 1].to_string())
+        }
+        value => Some(value.to_string()),
+    }
+}
@@ -0,0 +1,52 @@
+use serde_json::{Map, Value};
+use strum::{Display, EnumCount, FromRepr};
+
+use crate::value_type::ValueType;
+
+/// How object members are ordered on screen and when written out.
+///
+/// Arrays always keep their order since it carries meaning.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Display, FromRepr, EnumCount)]
+pub enum SortMode {
+    #[default]
+    #[strum(to_string = "insertion")]
+    Insertion,
+    #[strum(to_string = "alphabetical")]
+    Alphabetical,
+    #[strum(to_string = "by type")]
+    ByType,
+}
+
+impl SortMode {
+    pub fn next(self) -> Self {
+        let next_index = (self as usize + 1) % Self::COUNT;
+        Self::from_repr(next_index).unwrap_or(self)
+    }
+
+    /// The members of `map` in this order.
+    pub fn members(self, map: &Map<String, Value>) -> Vec<(&String, &Value)> {
+        let mut members: Vec<_> = map.iter().collect();
+        match self {
+            Self::Insertion => {}
+            Self::Alphabetical => members.sort_by_key(|(key, _)| *key),
+            Self::ByType => members.sort_by_key(|(_, value)| ValueType::of(value) as usize),
+        }
+        members
+    }
+
+    /// A copy of `value` with every nested object reordered.
+    pub fn apply(self, value: &Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                self.members(map)
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), self.apply(value)))
+                    .collect(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|item| self.apply(item)).collect())
+            }
+            value => value.clone(),
+        }
+    }
+}
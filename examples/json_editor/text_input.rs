@@ -0,0 +1,244 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// A single-line text field with a cursor and an optional selection.
+///
+/// Positions are byte offsets which always sit on grapheme boundaries,
+/// so combining marks and wide characters move and delete as one unit.
+#[derive(Default)]
+pub struct TextInput {
+    value: String,
+    cursor: usize,
+    /// The other end of the selection, the cursor being the moving end.
+    anchor: Option<usize>,
+}
+
+impl From<String> for TextInput {
+    fn from(value: String) -> Self {
+        Self {
+            cursor: value.len(),
+            value,
+            anchor: None,
+        }
+    }
+}
+
+impl TextInput {
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Applies an editing or movement key, holding Shift extends the selection.
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        let word = key.modifiers.contains(KeyModifiers::CONTROL);
+        let select = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Char(c) if !word => self.insert_str(c.encode_utf8(&mut [0; 4])),
+            KeyCode::Backspace => self.delete_to(self.previous_boundary(word)),
+            KeyCode::Delete => self.delete_to(self.next_boundary(word)),
+            KeyCode::Left => self.move_to(self.previous_boundary(word), select),
+            KeyCode::Right => self.move_to(self.next_boundary(word), select),
+            KeyCode::Home => self.move_to(0, select),
+            KeyCode::End => self.move_to(self.value.len(), select),
+            _ => {}
+        }
+    }
+
+    /// Inserts `text` at the cursor, replacing the selection.
+    ///
+    /// Line breaks become spaces since the field only holds a single line.
+    pub fn insert_str(&mut self, text: &str) {
+        self.delete_to(self.cursor);
+        let text = text.replace("\r\n", " ").replace(['\r', '\n'], " ");
+        self.value.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    fn move_to(&mut self, position: usize, select: bool) {
+        if !select {
+            self.anchor = None;
+        } else if self.anchor.is_none() {
+            self.anchor = Some(self.cursor);
+        }
+        self.cursor = position;
+    }
+
+    fn selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.anchor?;
+        (anchor != self.cursor).then(|| (anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+
+    /// Deletes the selection, or the text between the cursor and `position` without one.
+    fn delete_to(&mut self, position: usize) {
+        let (start, end) = self
+            .selection()
+            .unwrap_or((position.min(self.cursor), position.max(self.cursor)));
+        self.value.replace_range(start..end, "");
+        self.cursor = start;
+        self.anchor = None;
+    }
+
+    /// Start of the grapheme, or of the word, before the cursor.
+    fn previous_boundary(&self, word: bool) -> usize {
+        let mut graphemes = self.value[..self.cursor].grapheme_indices(true).rev();
+        if !word {
+            return graphemes.next().map_or(0, |(index, _)| index);
+        }
+        graphemes
+            .skip_while(|(_, grapheme)| !is_word(grapheme))
+            .take_while(|(_, grapheme)| is_word(grapheme))
+            .last()
+            .map_or(0, |(index, _)| index)
+    }
+
+    /// End of the grapheme, or of the word, after the cursor.
+    fn next_boundary(&self, word: bool) -> usize {
+        let rest = &self.value[self.cursor..];
+        let mut graphemes = rest.grapheme_indices(true);
+        let end = if word {
+            graphemes
+                .skip_while(|(_, grapheme)| !is_word(grapheme))
+                .find(|(_, grapheme)| !is_word(grapheme))
+                .map(|(index, _)| index)
+        } else {
+            graphemes.nth(1).map(|(index, _)| index)
+        };
+        self.cursor + end.unwrap_or(rest.len())
+    }
+
+    /// The part of the text fitting in `width` columns, scrolled to keep the cursor visible.
+    pub fn line(&self, width: u16, focused: bool) -> Line<'_> {
+        let width = usize::from(width.max(1));
+        let cursor_column = self.value[..self.cursor].width();
+        let cursor_width = self.value[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(1, UnicodeWidthStr::width);
+        let first_column = (cursor_column + cursor_width).saturating_sub(width);
+        let selection = self.selection().filter(|_| focused);
+
+        let mut spans = Vec::new();
+        let mut column = 0;
+        for (index, grapheme) in self.value.grapheme_indices(true) {
+            let start = column;
+            column += grapheme.width();
+            if start < first_column {
+                continue;
+            }
+            if column > first_column + width {
+                break;
+            }
+
+            let style = if focused && index == self.cursor {
+                Style::default().bg(Color::Black).fg(Color::White)
+            } else if selection.is_some_and(|(start, end)| (start..end).contains(&index)) {
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else {
+                Style::default()
+            };
+            spans.push(Span::styled(grapheme, style));
+        }
+        if focused && self.cursor == self.value.len() {
+            spans.push(Span::styled(" ", Style::default().bg(Color::Black)));
+        }
+
+        Line::from(spans)
+    }
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().any(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(value: &str, cursor: usize) -> TextInput {
+        TextInput {
+            value: value.to_string(),
+            cursor,
+            anchor: None,
+        }
+    }
+
+    fn press(input: &mut TextInput, code: KeyCode, modifiers: KeyModifiers) {
+        input.handle_key(KeyEvent::new(code, modifiers));
+    }
+
+    /// The cursor positions reached by pressing `code` with Ctrl until it stops moving.
+    fn word_stops(value: &str, cursor: usize, code: KeyCode) -> Vec<usize> {
+        let mut input = input(value, cursor);
+        let mut stops = Vec::new();
+        loop {
+            press(&mut input, code, KeyModifiers::CONTROL);
+            if stops.last() == Some(&input.cursor) {
+                return stops;
+            }
+            stops.push(input.cursor);
+        }
+    }
+
+    #[test]
+    fn ctrl_left_stops_at_word_starts() {
+        assert_eq!(word_stops("foo bar  baz", 12, KeyCode::Left), [9, 4, 0]);
+        assert_eq!(word_stops("a.b_c - d", 9, KeyCode::Left), [8, 2, 0]);
+        assert_eq!(word_stops("  ", 2, KeyCode::Left), [0]);
+    }
+
+    #[test]
+    fn ctrl_right_stops_at_word_ends() {
+        assert_eq!(word_stops("foo bar  baz", 0, KeyCode::Right), [3, 7, 12]);
+        assert_eq!(word_stops("a.b_c - d", 0, KeyCode::Right), [1, 5, 9]);
+        assert_eq!(word_stops("  ", 0, KeyCode::Right), [2]);
+    }
+
+    #[test]
+    fn words_include_non_ascii_letters() {
+        assert_eq!(word_stops("größe über", 0, KeyCode::Right), [7, 13]);
+        assert_eq!(word_stops("größe über", 13, KeyCode::Left), [8, 0]);
+    }
+
+    #[test]
+    fn ctrl_backspace_and_delete_remove_a_word() {
+        let mut text = input("hello world", 11);
+        press(&mut text, KeyCode::Backspace, KeyModifiers::CONTROL);
+        assert_eq!((text.value(), text.cursor), ("hello ", 6));
+        press(&mut text, KeyCode::Backspace, KeyModifiers::CONTROL);
+        assert_eq!((text.value(), text.cursor), ("", 0));
+
+        let mut text = input("  hi there", 0);
+        press(&mut text, KeyCode::Delete, KeyModifiers::CONTROL);
+        assert_eq!((text.value(), text.cursor), (" there", 0));
+    }
+
+    #[test]
+    fn graphemes_move_and_delete_as_one() {
+        let mut text = input("e\u{301}x", 4);
+        press(&mut text, KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(text.cursor, 3);
+        press(&mut text, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!((text.value(), text.cursor), ("x", 0));
+        assert_eq!(word_stops("e\u{301}x y", 0, KeyCode::Right), [4, 6]);
+    }
+
+    #[test]
+    fn shift_ctrl_selects_a_word() {
+        let mut text = input("key value", 9);
+        press(
+            &mut text,
+            KeyCode::Left,
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        assert_eq!(text.selection(), Some((4, 9)));
+        text.insert_str("v");
+        assert_eq!((text.value(), text.cursor), ("key v", 5));
+        press(&mut text, KeyCode::Char('w'), KeyModifiers::CONTROL);
+        assert_eq!(text.value(), "key v");
+    }
+}
@@ -0,0 +1,76 @@
+use serde_json::{Number, Value};
+use strum::{Display, EnumCount, FromRepr};
+
+/// The kinds of json value the editing popup can produce.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Display, FromRepr, EnumCount)]
+pub enum ValueType {
+    #[default]
+    #[strum(to_string = "string")]
+    String,
+    #[strum(to_string = "number")]
+    Number,
+    #[strum(to_string = "bool")]
+    Bool,
+    #[strum(to_string = "null")]
+    Null,
+    #[strum(to_string = "object")]
+    Object,
+    #[strum(to_string = "array")]
+    Array,
+}
+
+impl ValueType {
+    pub fn previous(self) -> Self {
+        let previous_index = (self as usize + Self::COUNT - 1) % Self::COUNT;
+        Self::from_repr(previous_index).unwrap_or(self)
+    }
+
+    pub fn next(self) -> Self {
+        let next_index = (self as usize + 1) % Self::COUNT;
+        Self::from_repr(next_index).unwrap_or(self)
+    }
+
+    pub fn of(value: &Value) -> Self {
+        match value {
+            Value::String(_) => Self::String,
+            Value::Number(_) => Self::Number,
+            Value::Bool(_) => Self::Bool,
+            Value::Null => Self::Null,
+            Value::Object(_) => Self::Object,
+            Value::Array(_) => Self::Array,
+        }
+    }
+
+    /// Parses the text typed in the Value box as this type.
+    ///
+    /// Objects and arrays accept inline json and start out empty when nothing is typed.
+    pub fn parse(self, input: &str) -> Result<Value, String> {
+        let trimmed = input.trim();
+        match self {
+            Self::String => Ok(Value::String(input.to_string())),
+            Self::Number => trimmed
+                .parse::<Number>()
+                .map(Value::Number)
+                .map_err(|_| format!("{trimmed:?} is not a number")),
+            Self::Bool => match trimmed {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                _ => Err("expected true or false".to_string()),
+            },
+            Self::Null => match trimmed {
+                "" | "null" => Ok(Value::Null),
+                _ => Err("null takes no value".to_string()),
+            },
+            Self::Object if trimmed.is_empty() => Ok(Value::Object(Default::default())),
+            Self::Array if trimmed.is_empty() => Ok(Value::Array(Vec::new())),
+            Self::Object | Self::Array => {
+                let value: Value = serde_json::from_str(trimmed).map_err(|err| err.to_string())?;
+                if Self::of(&value) == self {
+                    Ok(value)
+                } else {
+                    Err(format!("expected an {self}, found {}", Self::of(&value)))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use serde::Deserialize;
+
+use crate::task::TaskEvent;
+
+/// Everything that can change the state of [`App`].
+///
+/// Actions are sent on the action channel, by the event handlers as well as by background
+/// tasks, and applied one at a time by [`App::update`].
+///
+/// [`App`]: crate::app::App
+/// [`App::update`]: crate::app::App::update
+///
+/// Keys are bound to actions by their names in snake case, such as `focus_next`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// The terminal ticked.
+    #[serde(skip_deserializing)]
+    Tick,
+    /// Quit the application.
+    Quit,
+    /// Suspend the process until it is continued.
+    Suspend,
+    /// The process was continued after being suspended.
+    #[serde(skip_deserializing)]
+    Resume,
+    /// Give the focus to the next pane.
+    FocusNext,
+    /// Give the focus to the previous pane.
+    FocusPrevious,
+    /// Increment the counter.
+    Increment,
+    /// Decrement the counter.
+    Decrement,
+    /// Reset the counter to zero.
+    Reset,
+    /// Start a background task.
+    StartTask,
+    /// Cancel the selected background task.
+    CancelTask,
+    /// Select the previous item of the pane with the focus.
+    SelectPrevious,
+    /// Select the next item of the pane with the focus.
+    SelectNext,
+    /// A background task reported back.
+    #[serde(skip_deserializing)]
+    Task(TaskEvent),
+    /// The terminal window gained or lost focus.
+    #[serde(skip_deserializing)]
+    Focus(bool),
+    /// Text was pasted into the terminal.
+    #[serde(skip_deserializing)]
+    Paste(String),
+}
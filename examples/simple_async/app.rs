@@ -1,49 +1,143 @@
 use std::error;
 
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::Action,
+    components::{counter::Counter, status::Status, tasks::Tasks, Component},
+    event::Event,
+    handler::handle_events,
+    keymap::{Keymap, Lookup},
+    task::TaskRunner,
+};
+
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
 /// Application.
-#[derive(Debug)]
 pub struct App {
     /// Is the application running?
     pub running: bool,
-    /// counter
-    pub counter: u8,
+    /// Should the process be suspended before the next event?
+    pub should_suspend: bool,
+    /// The panes of the user interface, drawn side by side.
+    pub components: Vec<Box<dyn Component>>,
+    /// The index of the component with the focus, if any can take it.
+    pub focus: Option<usize>,
+    /// The key bindings.
+    pub keymap: Keymap,
 }
 
 impl Default for App {
     fn default() -> Self {
         Self {
             running: true,
-            counter: 0,
+            should_suspend: false,
+            // This is where you add new panes.
+            components: vec![
+                Box::new(Counter::default()),
+                Box::new(Status::default()),
+                Box::new(Tasks::default()),
+            ],
+            focus: None,
+            keymap: Keymap::default(),
         }
     }
 }
 
 impl App {
-    /// Constructs a new instance of [`App`].
-    pub fn new() -> Self {
-        Self::default()
+    /// Constructs a new instance of [`App`] with the given key bindings.
+    pub fn new(keymap: Keymap) -> Self {
+        Self {
+            keymap,
+            ..Self::default()
+        }
     }
 
-    /// Handles the tick event of the terminal.
-    pub fn tick(&self) {}
+    /// Initializes the components and gives the focus to the first one which can take it.
+    pub fn init(&mut self, action_tx: UnboundedSender<Action>, tasks: TaskRunner) -> AppResult<()> {
+        for component in &mut self.components {
+            component.init(action_tx.clone(), tasks.clone())?;
+        }
+        self.move_focus(true);
+        Ok(())
+    }
+
+    /// Maps an event to the [`Action`] it stands for, if any.
+    ///
+    /// The key bindings come first, then the components are asked in turn. Keys which are
+    /// part of a bound sequence are not passed on.
+    pub fn handle_event(&mut self, event: Event) -> Option<Action> {
+        if let Event::Key(key_event) = event {
+            match self.keymap.lookup(key_event) {
+                Lookup::Action(action) => return Some(action),
+                Lookup::Pending => return None,
+                Lookup::Unbound => {}
+            }
+        }
+        if let Some(action) = handle_events(&event) {
+            return Some(action);
+        }
+        let focus = self.focus;
+        self.components
+            .iter_mut()
+            .enumerate()
+            .filter(|(index, _)| !matches!(event, Event::Key(_)) || Some(*index) == focus)
+            .find_map(|(_, component)| component.handle_event(&event))
+    }
+
+    /// Applies an action, the only way the state of the application changes.
+    ///
+    /// Every component sees the action too. Returns the actions which follow from it,
+    /// to be applied next.
+    pub fn update(&mut self, action: Action) -> Vec<Action> {
+        match action {
+            Action::Quit => self.quit(),
+            Action::Suspend => self.suspend(),
+            Action::Resume => self.should_suspend = false,
+            Action::FocusNext => self.move_focus(true),
+            Action::FocusPrevious => self.move_focus(false),
+            _ => {}
+        }
+        self.components
+            .iter_mut()
+            .flat_map(|component| component.update(&action))
+            .collect()
+    }
 
     /// Set running to false to quit the application.
-    pub fn quit(&mut self) {
+    fn quit(&mut self) {
         self.running = false;
     }
 
-    pub fn increment_counter(&mut self) {
-        if let Some(res) = self.counter.checked_add(1) {
-            self.counter = res;
-        }
+    /// Asks for the process to be suspended, leaving the terminal to the shell.
+    fn suspend(&mut self) {
+        self.should_suspend = true;
     }
 
-    pub fn decrement_counter(&mut self) {
-        if let Some(res) = self.counter.checked_sub(1) {
-            self.counter = res;
+    /// Moves the focus to the next, or previous, component which can take it.
+    fn move_focus(&mut self, forward: bool) {
+        let count = self.components.len();
+        let start = match (self.focus, forward) {
+            (Some(focus), true) => focus + 1,
+            (Some(focus), false) => focus + count - 1,
+            (None, _) => 0,
+        };
+        let next = (0..count)
+            .map(|offset| {
+                if forward {
+                    (start + offset) % count
+                } else {
+                    (start + count - offset) % count
+                }
+            })
+            .find(|index| self.components[*index].focusable());
+        if let Some(focus) = self.focus {
+            self.components[focus].set_focus(false);
+        }
+        if let Some(next) = next {
+            self.components[next].set_focus(true);
         }
+        self.focus = next;
     }
 }
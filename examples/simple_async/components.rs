@@ -0,0 +1,46 @@
+use ratatui::{layout::Rect, Frame};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{action::Action, app::AppResult, event::Event, task::TaskRunner};
+
+pub mod counter;
+pub mod status;
+pub mod tasks;
+
+/// A pane of the user interface, with its own state.
+///
+/// Components live in the tree owned by [`App`], which forwards events and actions to them
+/// and gives each an area to draw into. Every method but [`Component::draw`] does nothing
+/// by default.
+///
+/// [`App`]: crate::app::App
+pub trait Component {
+    /// Called once before the first event, with a sender for actions from background tasks
+    /// and a runner to spawn tasks which report back as [`Action::Task`].
+    fn init(&mut self, _action_tx: UnboundedSender<Action>, _tasks: TaskRunner) -> AppResult<()> {
+        Ok(())
+    }
+
+    /// Maps an event to the [`Action`] it stands for, if any.
+    ///
+    /// Key events only reach the component with the focus, other events reach every one.
+    fn handle_event(&mut self, _event: &Event) -> Option<Action> {
+        None
+    }
+
+    /// Applies an action, returning the actions which follow from it.
+    fn update(&mut self, _action: &Action) -> Vec<Action> {
+        Vec::new()
+    }
+
+    /// Renders the component into `area`.
+    fn draw(&mut self, frame: &mut Frame, area: Rect);
+
+    /// Can the component take the focus, to receive key events?
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    /// Tells the component whether it has the focus.
+    fn set_focus(&mut self, _focused: bool) {}
+}
@@ -0,0 +1,81 @@
+use crossterm::event::MouseEventKind;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    widgets::{Block, BorderType, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{action::Action, components::Component, event::Event};
+
+/// A counter changed with the keys bound to its actions or the mouse wheel.
+#[derive(Debug, Default)]
+pub struct Counter {
+    /// The value of the counter.
+    pub value: u8,
+    /// Does the counter have the focus?
+    focused: bool,
+}
+
+impl Component for Counter {
+    fn handle_event(&mut self, event: &Event) -> Option<Action> {
+        match event {
+            Event::Mouse(mouse_event) => match mouse_event.kind {
+                MouseEventKind::ScrollUp => Some(Action::Increment),
+                MouseEventKind::ScrollDown => Some(Action::Decrement),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, action: &Action) -> Vec<Action> {
+        match action {
+            Action::Increment => self.value = self.value.saturating_add(1),
+            Action::Decrement => self.value = self.value.saturating_sub(1),
+            Action::Reset => self.value = 0,
+            // A pasted number sets the counter.
+            Action::Paste(text) => {
+                if let Ok(value) = text.trim().parse() {
+                    self.value = value;
+                }
+            }
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let border_color = if self.focused {
+            Color::Yellow
+        } else {
+            Color::Cyan
+        };
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Counter: {}\n\
+                    Press right and left to change it.\n\
+                    Press `g g` to reset it, or paste a number to set it.",
+                self.value
+            ))
+            .block(
+                Block::bordered()
+                    .title("Counter")
+                    .title_alignment(Alignment::Center)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .centered()
+            .wrap(Wrap { trim: true }),
+            area,
+        )
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+}
@@ -0,0 +1,58 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    widgets::{Block, BorderType, Paragraph},
+    Frame,
+};
+
+use crate::{action::Action, components::Component};
+
+/// Shows whether the terminal window has focus and what was pasted last.
+#[derive(Debug)]
+pub struct Status {
+    /// Does the terminal window have focus?
+    window_focused: bool,
+    /// The text pasted last, if any.
+    pasted: Option<String>,
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Self {
+            window_focused: true,
+            pasted: None,
+        }
+    }
+}
+
+impl Component for Status {
+    fn update(&mut self, action: &Action) -> Vec<Action> {
+        match action {
+            Action::Focus(focused) => self.window_focused = *focused,
+            Action::Paste(text) => self.pasted = Some(text.clone()),
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{}\nLast paste: {}",
+                if self.window_focused {
+                    "Focused"
+                } else {
+                    "Unfocused"
+                },
+                self.pasted.as_deref().unwrap_or("nothing yet"),
+            ))
+            .block(
+                Block::bordered()
+                    .title("Status")
+                    .title_alignment(Alignment::Center)
+                    .border_type(BorderType::Rounded),
+            )
+            .centered(),
+            area,
+        )
+    }
+}
@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph},
+    Frame,
+};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    action::Action,
+    app::AppResult,
+    components::Component,
+    event::Event,
+    task::{Outcome, TaskContext, TaskEvent, TaskId, TaskResult, TaskRunner, TaskUpdate},
+};
+
+/// The frames of the spinner shown next to running tasks.
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Lists the background tasks, running and finished, with the log of the selected one.
+#[derive(Debug)]
+pub struct Tasks {
+    /// Spawns the tasks, set on init.
+    runner: Option<TaskRunner>,
+    /// The tasks, oldest first.
+    tasks: Vec<Task>,
+    /// The index of the selected task.
+    selected: usize,
+    /// The frame of the spinner, advanced every tick.
+    spinner: usize,
+    /// Does the pane have the focus?
+    focused: bool,
+    /// Does the terminal window have focus? The spinner stands still while it does not.
+    window_focused: bool,
+}
+
+impl Default for Tasks {
+    fn default() -> Self {
+        Self {
+            runner: None,
+            tasks: Vec::new(),
+            selected: 0,
+            spinner: 0,
+            focused: false,
+            window_focused: true,
+        }
+    }
+}
+
+/// A background task as shown in the pane.
+#[derive(Debug)]
+struct Task {
+    id: TaskId,
+    name: String,
+    progress: u8,
+    log: Vec<String>,
+    /// How the task ended, `None` while it runs.
+    outcome: Option<Outcome>,
+    cancellation_token: CancellationToken,
+}
+
+impl Tasks {
+    /// Spawns the example job, which counts to twenty.
+    fn start(&mut self) {
+        let Some(runner) = &self.runner else {
+            return;
+        };
+        let (id, cancellation_token) = runner.spawn(count_to_twenty);
+        self.tasks.push(Task {
+            id,
+            name: format!("Count #{}", id.0),
+            progress: 0,
+            log: Vec::new(),
+            outcome: None,
+            cancellation_token,
+        });
+        self.selected = self.tasks.len() - 1;
+    }
+
+    fn record(&mut self, event: &TaskEvent) {
+        let Some(task) = self.tasks.iter_mut().find(|task| task.id == event.id) else {
+            return;
+        };
+        match &event.update {
+            TaskUpdate::Progress(progress) => task.progress = *progress,
+            TaskUpdate::Log(line) => task.log.push(line.clone()),
+            TaskUpdate::Finished(outcome) => task.outcome = Some(outcome.clone()),
+        }
+    }
+
+    fn line(&self, index: usize, task: &Task) -> Line<'static> {
+        let status = match &task.outcome {
+            None => format!(
+                "{} {:>3}%",
+                SPINNER[self.spinner % SPINNER.len()],
+                task.progress
+            ),
+            Some(Outcome::Done) => "done".to_string(),
+            Some(Outcome::Failed(_)) => "failed".to_string(),
+            Some(Outcome::Cancelled) => "cancelled".to_string(),
+        };
+        let line = Line::from(format!("{} {status}", task.name));
+        if index == self.selected && self.focused {
+            line.style(Style::default().add_modifier(Modifier::REVERSED))
+        } else {
+            line
+        }
+    }
+}
+
+impl Component for Tasks {
+    fn init(&mut self, _action_tx: UnboundedSender<Action>, tasks: TaskRunner) -> AppResult<()> {
+        self.runner = Some(tasks);
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) -> Option<Action> {
+        match event {
+            Event::Key(key_event) => match key_event.code {
+                KeyCode::Up => Some(Action::SelectPrevious),
+                KeyCode::Down => Some(Action::SelectNext),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, action: &Action) -> Vec<Action> {
+        match action {
+            Action::Tick if self.window_focused => self.spinner = self.spinner.wrapping_add(1),
+            Action::Focus(focused) => self.window_focused = *focused,
+            Action::StartTask => self.start(),
+            Action::CancelTask => {
+                if let Some(task) = self.tasks.get(self.selected) {
+                    task.cancellation_token.cancel();
+                }
+            }
+            Action::SelectPrevious if self.focused => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            Action::SelectNext if self.focused => {
+                self.selected = (self.selected + 1).min(self.tasks.len().saturating_sub(1));
+            }
+            Action::Task(event) => self.record(event),
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let border_color = if self.focused {
+            Color::Yellow
+        } else {
+            Color::Cyan
+        };
+        let block = Block::bordered()
+            .title("Tasks")
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(border_color));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [list, log] = Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(inner);
+        let lines: Vec<Line> = if self.tasks.is_empty() {
+            vec![Line::from("`t` starts a task.")]
+        } else {
+            self.tasks
+                .iter()
+                .enumerate()
+                .map(|(index, task)| self.line(index, task))
+                .collect()
+        };
+        // Keep the selected task in view.
+        let scroll = (self.selected + 1).saturating_sub(list.height.into());
+        frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), list);
+
+        let log_block = Block::default()
+            .borders(Borders::TOP)
+            .title("Log (`x` cancels)");
+        let mut log_lines: Vec<Line> = Vec::new();
+        if let Some(task) = self.tasks.get(self.selected) {
+            log_lines.extend(task.log.iter().map(|line| Line::from(line.as_str())));
+            if let Some(Outcome::Failed(err)) = &task.outcome {
+                log_lines.push(Line::from(format!("error: {err}")));
+            }
+        }
+        // Show the end of the log.
+        let log_height = log_block.inner(log).height as usize;
+        let skip = log_lines.len().saturating_sub(log_height);
+        frame.render_widget(
+            Paragraph::new(log_lines.split_off(skip)).block(log_block),
+            log,
+        );
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+}
+
+/// The example job, counting to twenty in a few seconds.
+async fn count_to_twenty(context: TaskContext) -> TaskResult {
+    for count in 1..=20u8 {
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        context.progress(count * 5);
+        if count % 5 == 0 {
+            context.log(format!("counted to {count}"));
+        }
+    }
+    Ok(())
+}
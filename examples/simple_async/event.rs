@@ -1,97 +1,377 @@
-use std::time::Duration;
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use crossterm::event::{Event as CrosstermEvent, KeyEvent, MouseEvent};
-use futures::{FutureExt, StreamExt};
-use tokio::sync::mpsc;
+use futures::{
+    stream::{BoxStream, Fuse},
+    FutureExt, StreamExt,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::mpsc,
+    time::{Instant, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
 
-use crate::app::AppResult;
+#[cfg(unix)]
+use tokio::signal::unix::{signal, Signal, SignalKind};
+
+use crate::{
+    app::AppResult,
+    session::{Recorded, Recorder, Replay, Session},
+    task::TaskEvent,
+};
 
 /// Terminal events.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Event {
     /// Terminal tick.
     Tick,
+    /// Time to draw the next frame.
+    Render,
     /// Key press.
     Key(KeyEvent),
     /// Mouse click/scroll.
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// The terminal window gained focus.
+    FocusGained,
+    /// The terminal window lost focus.
+    FocusLost,
+    /// Text pasted into the terminal.
+    Paste(String),
+    /// A background task reported back.
+    Task(TaskEvent),
+    /// The process was asked to stop, by SIGTSTP.
+    Suspend,
+    /// The process was asked to terminate, by SIGTERM or SIGHUP.
+    Terminate,
 }
 
 /// Terminal event handler.
-#[allow(dead_code)]
 #[derive(Debug)]
 pub struct EventHandler {
     /// Event sender channel.
     sender: mpsc::UnboundedSender<Event>,
     /// Event receiver channel.
     receiver: mpsc::UnboundedReceiver<Event>,
-    /// Event handler thread.
-    handler: tokio::task::JoinHandle<()>,
+    /// Event handler thread, `None` once it has finished.
+    handler: Option<tokio::task::JoinHandle<io::Result<()>>>,
+    /// Tells the event handler thread to stop.
+    cancellation_token: CancellationToken,
+    /// Is a [`Event::Render`] waiting to be received?
+    render_pending: Arc<AtomicBool>,
 }
 
 impl EventHandler {
     /// Constructs a new instance of [`EventHandler`].
-    pub fn new(tick_rate: u64) -> Self {
-        let tick_rate = Duration::from_millis(tick_rate);
+    ///
+    /// Both rates are in events per second. Frames are coalesced: a [`Event::Render`] is only
+    /// sent once the previous one has been received, so a slow draw never queues up frames.
+    ///
+    /// A replayed session is sent first, after which the terminal is read as usual, starting
+    /// with a resize to its actual size. When recording, the size of the terminal is recorded
+    /// first unless the replay starts with a resize.
+    pub fn new(frame_rate: f64, tick_rate: f64, session: Session) -> Self {
+        Self::with_input(frame_rate, tick_rate, session, Input::terminal())
+    }
+
+    /// Constructs an [`EventHandler`] which reads the given input instead of the terminal.
+    pub fn with_input(frame_rate: f64, tick_rate: f64, session: Session, input: Input) -> Self {
+        let tick_rate = Duration::from_secs_f64(1.0 / tick_rate);
+        let frame_rate = Duration::from_secs_f64(1.0 / frame_rate);
         let (sender, receiver) = mpsc::unbounded_channel();
+        let render_pending = Arc::new(AtomicBool::new(false));
         let _sender = sender.clone();
+        let _render_pending = render_pending.clone();
+        let cancellation_token = CancellationToken::new();
+        let _cancellation_token = cancellation_token.clone();
         let handler = tokio::spawn(async move {
-            let mut reader = crossterm::event::EventStream::new();
+            let mut outlet = Outlet {
+                sender: _sender,
+                recorder: session.recorder,
+            };
+            let mut signals = Signals::new()?;
+            let mut reader = input.events.fuse();
+            let starts_sized = session.replay.as_ref().is_some_and(|replay| {
+                matches!(
+                    replay.events.first(),
+                    Some(Recorded {
+                        event: Event::Resize(_, _),
+                        ..
+                    })
+                )
+            });
+            if outlet.recorder.is_some() && !starts_sized {
+                let (width, height) = (input.size)()?;
+                if !outlet.send(Event::Resize(width, height))? {
+                    return Ok(());
+                }
+            }
+            if let Some(replay) = session.replay {
+                let cancellation_token = &_cancellation_token;
+                if !play(
+                    replay,
+                    &mut outlet,
+                    &mut reader,
+                    &mut signals,
+                    cancellation_token,
+                )
+                .await?
+                {
+                    return Ok(());
+                }
+                // Back to the terminal, whose size may differ from the recorded one.
+                let (width, height) = (input.size)()?;
+                if !outlet.send(Event::Resize(width, height))? {
+                    return Ok(());
+                }
+            }
             let mut tick = tokio::time::interval(tick_rate);
+            // Ticks missed while suspended are dropped rather than delivered in a burst.
+            tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            let mut frame = tokio::time::interval(frame_rate);
+            frame.set_missed_tick_behavior(MissedTickBehavior::Skip);
             loop {
                 let tick_delay = tick.tick();
+                let frame_delay = frame.tick();
                 let crossterm_event = reader.next().fuse();
-                tokio::select! {
-                  _ = _sender.closed() => {
+                let event = tokio::select! {
+                  _ = _cancellation_token.cancelled() => {
                     break;
                   }
-                  _ = tick_delay => {
-                    _sender.send(Event::Tick).unwrap();
+                  _ = tick_delay => Event::Tick,
+                  _ = frame_delay => {
+                    if _render_pending.swap(true, Ordering::Relaxed) {
+                      continue;
+                    }
+                    Event::Render
                   }
-                  Some(Ok(evt)) = crossterm_event => {
-                    match evt {
-                      CrosstermEvent::Key(key) => {
-                        if key.kind == crossterm::event::KeyEventKind::Press {
-                          _sender.send(Event::Key(key)).unwrap();
-                        }
-                      },
-                      CrosstermEvent::Mouse(mouse) => {
-                        _sender.send(Event::Mouse(mouse)).unwrap();
-                      },
-                      CrosstermEvent::Resize(x, y) => {
-                        _sender.send(Event::Resize(x, y)).unwrap();
-                      },
-                      CrosstermEvent::FocusLost => {
-                      },
-                      CrosstermEvent::FocusGained => {
-                      },
-                      CrosstermEvent::Paste(_) => {
-                      },
+                  event = signals.recv() => event,
+                  crossterm_event = crossterm_event => match crossterm_event {
+                    Some(Ok(CrosstermEvent::Key(key))) => {
+                      if key.kind != crossterm::event::KeyEventKind::Press {
+                        continue;
+                      }
+                      Event::Key(key)
                     }
+                    Some(Ok(CrosstermEvent::Mouse(mouse))) => Event::Mouse(mouse),
+                    Some(Ok(CrosstermEvent::Resize(x, y))) => Event::Resize(x, y),
+                    Some(Ok(CrosstermEvent::FocusLost)) => Event::FocusLost,
+                    Some(Ok(CrosstermEvent::FocusGained)) => Event::FocusGained,
+                    Some(Ok(CrosstermEvent::Paste(text))) => Event::Paste(text),
+                    Some(Err(err)) => return Err(err),
+                    None => break,
                   }
                 };
+                if !outlet.send(event)? {
+                    break;
+                }
+            }
+            Ok(())
+        });
+        Self {
+            sender,
+            receiver,
+            handler: Some(handler),
+            cancellation_token,
+            render_pending,
+        }
+    }
+
+    /// Constructs an [`EventHandler`] which sends the given events, then ends.
+    ///
+    /// Nothing is read from the terminal, and there are no ticks or frames other than the
+    /// scripted ones, so that the main loop can be driven deterministically.
+    #[cfg(test)]
+    pub fn scripted(events: impl IntoIterator<Item = Event>) -> Self {
+        let events: Vec<Event> = events.into_iter().collect();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let _sender = sender.clone();
+        let handler = tokio::spawn(async move {
+            for event in events {
+                if _sender.send(event).is_err() {
+                    break;
+                }
             }
+            Ok(())
         });
         Self {
             sender,
             receiver,
-            handler,
+            handler: Some(handler),
+            cancellation_token: CancellationToken::new(),
+            render_pending: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Returns a sender for events from elsewhere than the terminal, such as background tasks.
+    pub fn sender(&self) -> mpsc::UnboundedSender<Event> {
+        self.sender.clone()
+    }
+
     /// Receive the next event from the handler thread.
     ///
     /// This function will always block the current thread if
     /// there is no data available and it's possible for more data to be sent.
+    ///
+    /// Fails with the error the terminal reader stopped on, if it stopped.
     pub async fn next(&mut self) -> AppResult<Event> {
-        self.receiver
-            .recv()
-            .await
-            .ok_or(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "This is an IO error",
-            )))
+        let Some(handler) = self.handler.as_mut() else {
+            return Err(Self::stopped());
+        };
+        let event = tokio::select! {
+            biased;
+            Some(event) = self.receiver.recv() => event,
+            result = handler => {
+                self.handler = None;
+                result??;
+                return Err(Self::stopped());
+            }
+        };
+        if let Event::Render = event {
+            self.render_pending.store(false, Ordering::Relaxed);
+        }
+        Ok(event)
+    }
+
+    /// Stops the event handler thread and waits for it to finish.
+    ///
+    /// Fails with the error the terminal reader stopped on, if any.
+    pub async fn stop(&mut self) -> AppResult<()> {
+        self.cancellation_token.cancel();
+        if let Some(handler) = self.handler.take() {
+            handler.await??;
+        }
+        Ok(())
+    }
+
+    fn stopped() -> Box<dyn std::error::Error> {
+        Box::new(io::Error::other("the terminal event stream has ended"))
+    }
+}
+
+/// Sends events to the receiver, recording them on the way.
+struct Outlet {
+    sender: mpsc::UnboundedSender<Event>,
+    recorder: Option<Recorder>,
+}
+
+impl Outlet {
+    /// Sends the event, returning whether the receiver is still there to listen.
+    fn send(&mut self, event: Event) -> io::Result<bool> {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(&event)?;
+        }
+        Ok(self.sender.send(event).is_ok())
+    }
+}
+
+/// Where the terminal events and the size of the terminal come from.
+///
+/// That is the terminal itself, but for tests.
+pub struct Input {
+    pub events: BoxStream<'static, io::Result<CrosstermEvent>>,
+    pub size: fn() -> io::Result<(u16, u16)>,
+}
+
+impl Input {
+    /// Reads the terminal the application runs in.
+    pub fn terminal() -> Self {
+        Self {
+            events: crossterm::event::EventStream::new().boxed(),
+            size: crossterm::terminal::size,
+        }
+    }
+}
+
+/// The terminal events of an [`Input`], as read.
+type Reader = Fuse<BoxStream<'static, io::Result<CrosstermEvent>>>;
+
+/// Sends the events of a recorded session, when they are due.
+///
+/// Signals and key presses on the terminal are still handled meanwhile, so that the quit
+/// keys stop a long replay. Returns whether to go on reading the terminal.
+async fn play(
+    replay: Replay,
+    outlet: &mut Outlet,
+    reader: &mut Reader,
+    signals: &mut Signals,
+    cancellation_token: &CancellationToken,
+) -> io::Result<bool> {
+    let start = Instant::now();
+    let mut next = 0;
+    while let Some(recorded) = replay.events.get(next) {
+        let due = start + replay.due(recorded);
+        let event = tokio::select! {
+            _ = cancellation_token.cancelled() => return Ok(false),
+            event = signals.recv() => event,
+            Some(crossterm_event) = reader.next() => match crossterm_event? {
+                CrosstermEvent::Key(key) if key.kind == crossterm::event::KeyEventKind::Press => {
+                    Event::Key(key)
+                }
+                // The replay decides on the rest, such as the size.
+                _ => continue,
+            },
+            _ = tokio::time::sleep_until(due) => {
+                next += 1;
+                recorded.event.clone()
+            }
+        };
+        if !outlet.send(event)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// The signals the application reacts to.
+#[cfg(unix)]
+struct Signals {
+    terminate: Signal,
+    hangup: Signal,
+    suspend: Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    fn new() -> io::Result<Self> {
+        Ok(Self {
+            terminate: signal(SignalKind::terminate())?,
+            hangup: signal(SignalKind::hangup())?,
+            // Handling SIGTSTP keeps the process running until the terminal has been reset.
+            suspend: signal(SignalKind::from_raw(signal_hook::consts::SIGTSTP))?,
+        })
+    }
+
+    /// Waits for the next signal, returning the event it stands for.
+    async fn recv(&mut self) -> Event {
+        tokio::select! {
+            _ = self.terminate.recv() => Event::Terminate,
+            _ = self.hangup.recv() => Event::Terminate,
+            _ = self.suspend.recv() => Event::Suspend,
+        }
+    }
+}
+
+/// There are no signals to react to on this platform.
+#[cfg(not(unix))]
+struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    fn new() -> io::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn recv(&mut self) -> Event {
+        std::future::pending().await
     }
 }
@@ -1,28 +1,19 @@
-use crate::app::{App, AppResult};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::{action::Action, event::Event};
 
-/// Handles the key events and updates the state of [`App`].
-pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
-    match key_event.code {
-        // Exit application on `ESC` or `q`
-        KeyCode::Esc | KeyCode::Char('q') => {
-            app.quit();
-        }
-        // Exit application on `Ctrl-C`
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            if key_event.modifiers == KeyModifiers::CONTROL {
-                app.quit();
-            }
-        }
-        // Counter handlers
-        KeyCode::Right | KeyCode::Char('j') => {
-            app.increment_counter();
-        }
-        KeyCode::Left | KeyCode::Char('k') => {
-            app.decrement_counter();
-        }
-        // Other handlers you could add here.
-        _ => {}
+/// Maps an event other than a key to the application wide [`Action`] it stands for, if any.
+///
+/// Keys are looked up in the [`Keymap`]. Events left unmapped are passed on to the components.
+///
+/// [`Keymap`]: crate::keymap::Keymap
+pub fn handle_events(event: &Event) -> Option<Action> {
+    match event {
+        Event::Tick => Some(Action::Tick),
+        Event::FocusGained => Some(Action::Focus(true)),
+        Event::FocusLost => Some(Action::Focus(false)),
+        Event::Paste(text) => Some(Action::Paste(text.clone())),
+        Event::Suspend => Some(Action::Suspend),
+        Event::Terminate => Some(Action::Quit),
+        Event::Task(task_event) => Some(Action::Task(task_event.clone())),
+        Event::Render | Event::Key(_) | Event::Mouse(_) | Event::Resize(_, _) => None,
     }
-    Ok(())
 }
@@ -0,0 +1,198 @@
+use std::{collections::HashMap, env, fs, io, path::PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{action::Action, app::AppResult};
+
+/// The bindings compiled in, which the config file can override.
+///
+/// A binding is a sequence of keys separated by spaces, each either a single character or
+/// a named key between angle brackets, optionally with `Ctrl-`, `Alt-` or `Shift-` in front.
+const DEFAULT_BINDINGS: &str = r#"
+"q" = "quit"
+"<Esc>" = "quit"
+"<Ctrl-c>" = "quit"
+"<Ctrl-C>" = "quit"
+"<Ctrl-z>" = "suspend"
+"<Ctrl-Z>" = "suspend"
+"<Tab>" = "focus_next"
+"<BackTab>" = "focus_previous"
+"<Right>" = "increment"
+"k" = "increment"
+"<Left>" = "decrement"
+"j" = "decrement"
+"g g" = "reset"
+"t" = "start_task"
+"x" = "cancel_task"
+"#;
+
+/// What a key typed means, given the keys typed before it.
+pub enum Lookup {
+    /// The key completes a sequence bound to this action.
+    Action(Action),
+    /// The key starts or continues a bound sequence, more keys are needed.
+    Pending,
+    /// The key is not bound to anything.
+    Unbound,
+}
+
+/// Key sequences and the actions they are bound to.
+#[derive(Debug)]
+pub struct Keymap {
+    bindings: HashMap<Vec<KeyEvent>, Action>,
+    /// The keys typed so far of a sequence longer than one key.
+    pending: Vec<KeyEvent>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Self {
+            bindings: HashMap::new(),
+            pending: Vec::new(),
+        };
+        let bindings = toml::from_str(DEFAULT_BINDINGS).expect("the default bindings are valid");
+        keymap
+            .bind(bindings)
+            .expect("the default bindings are valid");
+        keymap
+    }
+}
+
+impl Keymap {
+    /// Loads the bindings of the config file on top of the defaults.
+    ///
+    /// The config file is `simple_async/keymap.toml`, or `keymap.json`, in the XDG config
+    /// directory. Both map key sequences to action names, such as `"g g" = "reset"`.
+    pub fn load() -> AppResult<Self> {
+        let mut keymap = Self::default();
+        let Some(dir) = config_dir() else {
+            return Ok(keymap);
+        };
+        for name in ["keymap.toml", "keymap.json"] {
+            let path = dir.join(name);
+            let text = match fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(format!("{}: {err}", path.display()).into()),
+            };
+            let bindings = if name.ends_with(".json") {
+                serde_json::from_str(&text).map_err(|err| err.to_string())
+            } else {
+                toml::from_str(&text).map_err(|err| err.to_string())
+            };
+            bindings
+                .and_then(|bindings| keymap.bind(bindings))
+                .map_err(|err| format!("{}: {err}", path.display()))?;
+        }
+        Ok(keymap)
+    }
+
+    /// Adds bindings, replacing those of the same key sequences.
+    fn bind(&mut self, bindings: HashMap<String, Action>) -> Result<(), String> {
+        for (keys, action) in bindings {
+            self.bindings.insert(parse_keys(&keys)?, action);
+        }
+        Ok(())
+    }
+
+    /// Looks up a typed key, following on from the keys typed before it.
+    pub fn lookup(&mut self, key_event: KeyEvent) -> Lookup {
+        let key_event = normalize(key_event);
+        self.pending.push(key_event);
+        if let Some(action) = self.bindings.get(&self.pending) {
+            self.pending.clear();
+            return Lookup::Action(action.clone());
+        }
+        if self
+            .bindings
+            .keys()
+            .any(|keys| keys.starts_with(&self.pending))
+        {
+            return Lookup::Pending;
+        }
+        // The sequence leads nowhere, so start over from the key on its own.
+        let restart = self.pending.len() > 1;
+        self.pending.clear();
+        if restart {
+            self.lookup(key_event)
+        } else {
+            Lookup::Unbound
+        }
+    }
+}
+
+/// The directory holding the config files, if there is a home to find it in.
+fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("simple_async"))
+}
+
+/// Drops what does not take part in matching a key against the bindings.
+///
+/// Characters already tell whether shift was held, as does `BackTab`.
+fn normalize(key_event: KeyEvent) -> KeyEvent {
+    let mut modifiers = key_event.modifiers;
+    if matches!(key_event.code, KeyCode::Char(_) | KeyCode::BackTab) {
+        modifiers.remove(KeyModifiers::SHIFT);
+    }
+    KeyEvent::new(key_event.code, modifiers)
+}
+
+/// Parses a sequence of keys separated by spaces, such as `g g` or `<Ctrl-x> s`.
+fn parse_keys(keys: &str) -> Result<Vec<KeyEvent>, String> {
+    let sequence = keys
+        .split_whitespace()
+        .map(|key| parse_key(key).ok_or_else(|| format!("invalid key `{key}` in `{keys}`")))
+        .collect::<Result<Vec<_>, _>>()?;
+    if sequence.is_empty() {
+        return Err("empty key sequence".to_string());
+    }
+    Ok(sequence)
+}
+
+/// Parses a single character, or a named key with modifiers such as `<Ctrl-c>`.
+fn parse_key(key: &str) -> Option<KeyEvent> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+    let inner = key.strip_prefix('<')?.strip_suffix('>')?;
+    let mut modifiers = KeyModifiers::NONE;
+    let mut name = inner;
+    while let Some((modifier, rest)) = name.split_once('-').filter(|(_, rest)| !rest.is_empty()) {
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" | "c" => KeyModifiers::CONTROL,
+            "alt" | "a" => KeyModifiers::ALT,
+            "shift" | "s" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+        name = rest;
+    }
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match name.to_lowercase().as_str() {
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "space" => KeyCode::Char(' '),
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            function => KeyCode::F(function.strip_prefix('f')?.parse().ok()?),
+        },
+    };
+    Some(normalize(KeyEvent::new(code, modifiers)))
+}
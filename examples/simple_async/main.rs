@@ -1,46 +1,145 @@
-use std::io;
+use std::{io, path::PathBuf};
 
-use ratatui::{backend::CrosstermBackend, Terminal};
+use argh::FromArgs;
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    Terminal,
+};
+use tokio::sync::mpsc;
 
 use crate::{
+    action::Action,
     app::{App, AppResult},
     event::{Event, EventHandler},
-    handler::handle_key_events,
+    keymap::Keymap,
+    session::{Recorder, Replay, Session},
+    task::TaskRunner,
     tui::Tui,
 };
 
+pub mod action;
 pub mod app;
+pub mod components;
 pub mod event;
 pub mod handler;
+pub mod keymap;
+pub mod session;
+pub mod task;
 pub mod tui;
 pub mod ui;
 
+#[cfg(test)]
+mod tests;
+
+/// Frames drawn per second, at most.
+const FRAME_RATE: f64 = 30.0;
+
+/// Ticks per second, each sending an [`Action::Tick`].
+const TICK_RATE: f64 = 4.0;
+
+/// Simple Async
+#[derive(Debug, FromArgs)]
+struct Cli {
+    /// record the events of the session to this file, to replay it later.
+    #[argh(option)]
+    record: Option<PathBuf>,
+
+    /// replay the session recorded in this file, then go on reading the terminal. Keys typed
+    /// meanwhile are handled as usual, so the quit keys stop the replay.
+    #[argh(option)]
+    replay: Option<PathBuf>,
+
+    /// replay as fast as possible rather than in real time.
+    #[argh(switch)]
+    fast: bool,
+}
+
 #[tokio::main]
 async fn main() -> AppResult<()> {
+    let cli: Cli = argh::from_env();
+
+    // Load the key bindings and the session, before the terminal is taken over to report
+    // errors plainly.
+    let (keymap, session) = match load(&cli) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("simple_async: {err}");
+            std::process::exit(1);
+        }
+    };
+
     // Create an application.
-    let mut app = App::new();
+    let mut app = App::new(keymap);
+
+    // Create the action channel. Background tasks get a clone of the sender to send actions too.
+    let (action_tx, action_rx) = mpsc::unbounded_channel();
+    let events = EventHandler::new(FRAME_RATE, TICK_RATE, session);
+    app.init(action_tx.clone(), TaskRunner::new(events.sender()))?;
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stdout());
     let terminal = Terminal::new(backend)?;
-    let events = EventHandler::new(250);
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
 
-    // Start the main loop.
+    // Run the application, resetting the terminal even when it fails.
+    let result = run(&mut app, &mut tui, action_tx, action_rx).await;
+
+    // Exit the user interface.
+    tui.exit().await?;
+    result
+}
+
+/// Loads the key bindings, and the files to record and replay the session.
+fn load(cli: &Cli) -> AppResult<(Keymap, Session)> {
+    let keymap = Keymap::load()?;
+    let session = Session {
+        recorder: cli.record.as_deref().map(Recorder::create).transpose()?,
+        replay: cli
+            .replay
+            .as_deref()
+            .map(|path| Replay::open(path, !cli.fast))
+            .transpose()?,
+    };
+    Ok((keymap, session))
+}
+
+/// Runs the main loop until the application quits.
+///
+/// Events are mapped to actions and sent on the action channel, from which every action,
+/// whoever sent it, is applied to the application. Pending actions are applied before the
+/// next event is handled.
+async fn run<B: Backend>(
+    app: &mut App,
+    tui: &mut Tui<B>,
+    action_tx: mpsc::UnboundedSender<Action>,
+    mut action_rx: mpsc::UnboundedReceiver<Action>,
+) -> AppResult<()> {
     while app.running {
-        // Render the user interface.
-        tui.draw(&mut app)?;
-        // Handle events.
-        match tui.events.next().await? {
-            Event::Tick => app.tick(),
-            Event::Key(key_event) => handle_key_events(key_event, &mut app)?,
-            Event::Mouse(_) => {}
-            Event::Resize(_, _) => {}
+        tokio::select! {
+            biased;
+            Some(action) = action_rx.recv() => {
+                for action in app.update(action) {
+                    action_tx.send(action)?;
+                }
+            }
+            event = tui.events.next() => match event? {
+                // Render the user interface only when a frame is due.
+                Event::Render => tui.draw(app)?,
+                Event::Resize(width, height) => tui.resize(width, height),
+                event => {
+                    if let Some(action) = app.handle_event(event) {
+                        action_tx.send(action)?;
+                    }
+                }
+            },
+        }
+        if app.should_suspend {
+            tui.suspend()?;
+            for action in app.update(Action::Resume) {
+                action_tx.send(action)?;
+            }
         }
     }
-
-    // Exit the user interface.
-    tui.exit()?;
     Ok(())
 }
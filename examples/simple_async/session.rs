@@ -0,0 +1,100 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{app::AppResult, event::Event};
+
+/// Where the events of an [`EventHandler`] come from besides the terminal, and where they go.
+///
+/// [`EventHandler`]: crate::event::EventHandler
+#[derive(Debug, Default)]
+pub struct Session {
+    /// Records every event but those of background tasks.
+    pub recorder: Option<Recorder>,
+    /// A recorded session fed back in before the terminal is read.
+    pub replay: Option<Replay>,
+}
+
+/// An event as recorded, one per line of a recording.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Recorded {
+    /// Microseconds since the recording started.
+    pub at_us: u64,
+    pub event: Event,
+}
+
+/// Writes events to a file, with the time since the recording started.
+///
+/// Background tasks report back outside of the recording, since replaying the session
+/// starts the same tasks again.
+#[derive(Debug)]
+pub struct Recorder {
+    file: BufWriter<File>,
+    start: Instant,
+}
+
+impl Recorder {
+    /// Creates the recording, replacing any file at `path`.
+    pub fn create(path: &Path) -> AppResult<Self> {
+        let file = File::create(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        Ok(Self {
+            file: BufWriter::new(file),
+            start: Instant::now(),
+        })
+    }
+
+    /// Writes the event, flushing it so that the recording survives a crash.
+    pub fn record(&mut self, event: &Event) -> io::Result<()> {
+        let recorded = Recorded {
+            at_us: self
+                .start
+                .elapsed()
+                .as_micros()
+                .try_into()
+                .unwrap_or(u64::MAX),
+            event: event.clone(),
+        };
+        serde_json::to_writer(&mut self.file, &recorded)?;
+        writeln!(self.file)?;
+        self.file.flush()
+    }
+}
+
+/// A recorded session to feed back in.
+#[derive(Debug)]
+pub struct Replay {
+    pub events: Vec<Recorded>,
+    /// Keep to the recorded times, rather than going as fast as possible.
+    pub real_time: bool,
+}
+
+impl Replay {
+    /// Reads the recording at `path`.
+    pub fn open(path: &Path, real_time: bool) -> AppResult<Self> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        let events = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line)
+                    .map_err(|err| format!("{}:{}: {err}", path.display(), index + 1))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { events, real_time })
+    }
+
+    /// When the event is due, counted from the start of the replay.
+    pub fn due(&self, recorded: &Recorded) -> Duration {
+        if self.real_time {
+            Duration::from_micros(recorded.at_us)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    error,
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::sync::CancellationToken;
+
+use crate::event::Event;
+
+/// The result of a job, whose error must be sendable across threads.
+pub type TaskResult = Result<(), Box<dyn error::Error + Send + Sync>>;
+
+/// Identifies a task among those spawned by the same [`TaskRunner`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TaskId(pub usize);
+
+/// What a task reports back, sent as an [`Event::Task`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskEvent {
+    pub id: TaskId,
+    pub update: TaskUpdate,
+}
+
+/// One report of a task.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskUpdate {
+    /// How much of the job is done, in percent.
+    Progress(u8),
+    /// A line for the log of the task.
+    Log(String),
+    /// The task has ended, always the last report.
+    Finished(Outcome),
+}
+
+/// How a task ended.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+/// Spawns jobs onto the runtime, which report back through the event channel.
+#[derive(Clone, Debug)]
+pub struct TaskRunner {
+    /// Event sender channel.
+    sender: UnboundedSender<Event>,
+    /// The id of the next task.
+    next_id: Arc<AtomicUsize>,
+}
+
+impl TaskRunner {
+    /// Constructs a new instance of [`TaskRunner`], reporting on the given event channel.
+    pub fn new(sender: UnboundedSender<Event>) -> Self {
+        Self {
+            sender,
+            next_id: Arc::new(AtomicUsize::new(1)),
+        }
+    }
+
+    /// Spawns a job, returning the id of its task and the token which cancels it.
+    ///
+    /// The job reports its progress and log lines through the [`TaskContext`] it is given,
+    /// its completion is reported once it returns or is cancelled.
+    pub fn spawn<F, Fut>(&self, job: F) -> (TaskId, CancellationToken)
+    where
+        F: FnOnce(TaskContext) -> Fut,
+        Fut: Future<Output = TaskResult> + Send + 'static,
+    {
+        let id = TaskId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let cancellation_token = CancellationToken::new();
+        let context = TaskContext {
+            id,
+            sender: self.sender.clone(),
+        };
+        let job = job(context.clone());
+        let _cancellation_token = cancellation_token.clone();
+        tokio::spawn(async move {
+            let outcome = tokio::select! {
+                _ = _cancellation_token.cancelled() => Outcome::Cancelled,
+                result = job => match result {
+                    Ok(()) => Outcome::Done,
+                    Err(err) => Outcome::Failed(err.to_string()),
+                },
+            };
+            context.send(TaskUpdate::Finished(outcome));
+        });
+        (id, cancellation_token)
+    }
+}
+
+/// Lets a running job report back to the application.
+#[derive(Clone, Debug)]
+pub struct TaskContext {
+    id: TaskId,
+    sender: UnboundedSender<Event>,
+}
+
+impl TaskContext {
+    /// Reports how much of the job is done, in percent.
+    pub fn progress(&self, percent: u8) {
+        self.send(TaskUpdate::Progress(percent.min(100)));
+    }
+
+    /// Adds a line to the log of the task.
+    pub fn log(&self, line: impl Into<String>) {
+        self.send(TaskUpdate::Log(line.into()));
+    }
+
+    fn send(&self, update: TaskUpdate) {
+        // Once the event loop is gone, there is nobody left to report to.
+        let _ = self.sender.send(Event::Task(TaskEvent {
+            id: self.id,
+            update,
+        }));
+    }
+}
@@ -0,0 +1,346 @@
+//! End to end tests, running the main loop headless over scripted events.
+//!
+//! The rendered screens are compared with the snapshots in `snapshots/`. Run the tests with
+//! `UPDATE_SNAPSHOTS=1` to write the snapshots from the current output after a deliberate
+//! change, then review the difference.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{Event as CrosstermEvent, KeyCode, KeyEvent, KeyModifiers};
+use futures::{stream, StreamExt};
+use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+use tokio::sync::mpsc;
+
+use crate::{
+    app::{App, AppResult},
+    event::{Event, EventHandler, Input},
+    keymap::Keymap,
+    run,
+    session::{Recorded, Recorder, Replay, Session},
+    task::TaskRunner,
+    tui::Tui,
+    FRAME_RATE, TICK_RATE,
+};
+
+/// The size of the headless terminal.
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 20;
+
+/// Runs the main loop on a [`TestBackend`] over the scripted events.
+///
+/// Fails if the script ends before the application quits.
+async fn run_script(events: impl IntoIterator<Item = Event>) -> AppResult<Tui<TestBackend>> {
+    run_events(EventHandler::scripted(events)).await
+}
+
+/// Runs the main loop on a [`TestBackend`] until the application quits.
+///
+/// The default key bindings are used, never the config file.
+async fn run_events(events: EventHandler) -> AppResult<Tui<TestBackend>> {
+    let mut app = App::new(Keymap::default());
+    let (action_tx, action_rx) = mpsc::unbounded_channel();
+    app.init(action_tx.clone(), TaskRunner::new(events.sender()))?;
+    let terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT))?;
+    let mut tui = Tui::new(terminal, events);
+    run(&mut app, &mut tui, action_tx, action_rx).await?;
+    tui.events.stop().await?;
+    Ok(tui)
+}
+
+/// Runs the scripted events, then renders and quits, returning the screen.
+async fn render_script(events: impl IntoIterator<Item = Event>) -> Buffer {
+    let events = events.into_iter().chain([Event::Render, key('q')]);
+    let tui = run_script(events).await.expect("the script runs");
+    tui.backend().buffer().clone()
+}
+
+/// A key press without modifiers.
+fn key(c: char) -> Event {
+    Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+}
+
+/// The key presses typing `text`.
+fn keys(text: &str) -> Vec<Event> {
+    text.chars().map(key).collect()
+}
+
+/// Terminal input typing `text`, then nothing, from a terminal of the headless size.
+fn input(text: &str) -> Input {
+    let events: Vec<_> = text
+        .chars()
+        .map(|c| {
+            Ok(CrosstermEvent::Key(KeyEvent::new(
+                KeyCode::Char(c),
+                KeyModifiers::NONE,
+            )))
+        })
+        .collect();
+    Input {
+        events: stream::iter(events).chain(stream::pending()).boxed(),
+        size: || Ok((WIDTH, HEIGHT)),
+    }
+}
+
+/// A path for a recording, unique to the test.
+fn recording_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("simple_async-{}-{name}.jsonl", std::process::id()))
+}
+
+/// The events of a recording, which is removed.
+fn take_recording(path: &Path) -> Vec<Event> {
+    let replay = Replay::open(path, false).expect("the recording can be read");
+    fs::remove_file(path).expect("the recording can be removed");
+    replay
+        .events
+        .into_iter()
+        .map(|recorded| recorded.event)
+        .collect()
+}
+
+/// A replay of the events, all recorded at the start.
+fn replay(events: Vec<Event>, real_time: bool) -> Replay {
+    Replay {
+        events: events
+            .into_iter()
+            .map(|event| Recorded { at_us: 0, event })
+            .collect(),
+        real_time,
+    }
+}
+
+/// The symbols of the buffer, one line per row.
+fn buffer_text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    (area.top()..area.bottom())
+        .map(|y| {
+            let row: String = (area.left()..area.right())
+                .map(|x| buffer[(x, y)].symbol())
+                .collect();
+            row + "\n"
+        })
+        .collect()
+}
+
+/// Asserts that the buffer matches the snapshot `name`, or writes it when
+/// `UPDATE_SNAPSHOTS` is set.
+#[track_caller]
+fn assert_snapshot(buffer: &Buffer, name: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("examples/simple_async/snapshots")
+        .join(format!("{name}.txt"));
+    let actual = buffer_text(buffer);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().expect("snapshots have a directory"))
+            .expect("the snapshot directory can be created");
+        fs::write(&path, actual).expect("the snapshot can be written");
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "{}: {err}, run with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        actual == expected,
+        "the screen differs from {}\n\nexpected:\n{expected}\nactual:\n{actual}",
+        path.display()
+    );
+}
+
+/// Asserts that some row of the buffer contains `text`.
+#[track_caller]
+fn assert_contains(buffer: &Buffer, text: &str) {
+    let screen = buffer_text(buffer);
+    assert!(
+        screen.lines().any(|line| line.contains(text)),
+        "no line contains {text:?}:\n{screen}"
+    );
+}
+
+#[tokio::test]
+async fn initial_screen() {
+    let buffer = render_script([]).await;
+    assert_snapshot(&buffer, "initial_screen");
+}
+
+#[tokio::test]
+async fn keys_change_the_counter() {
+    let buffer = render_script(keys("kkkj")).await;
+    assert_snapshot(&buffer, "counter_changed");
+}
+
+#[tokio::test]
+async fn arrows_change_the_counter() {
+    let right = Event::Key(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE));
+    let left = Event::Key(KeyEvent::new(KeyCode::Left, KeyModifiers::NONE));
+    let buffer = render_script([right.clone(), right, left.clone(), left.clone(), left]).await;
+    assert_contains(&buffer, "Counter: 0");
+}
+
+#[tokio::test]
+async fn key_sequence_resets_the_counter() {
+    let buffer = render_script(keys("kkkgg")).await;
+    assert_contains(&buffer, "Counter: 0");
+}
+
+#[tokio::test]
+async fn broken_key_sequence_starts_over() {
+    let buffer = render_script(keys("kgkgxk")).await;
+    assert_contains(&buffer, "Counter: 3");
+}
+
+#[tokio::test]
+async fn paste_sets_the_counter_and_status() {
+    let buffer = render_script([Event::Paste("42".to_string()), Event::FocusLost]).await;
+    assert_snapshot(&buffer, "pasted");
+}
+
+#[tokio::test]
+async fn spinner_stands_still_while_unfocused() {
+    let buffer = render_script([key('t'), Event::Tick, Event::Tick]).await;
+    assert_contains(&buffer, "Count #1 -   0%");
+
+    let buffer = render_script([
+        key('t'),
+        Event::FocusLost,
+        Event::Tick,
+        Event::Tick,
+        Event::FocusGained,
+        Event::Tick,
+    ])
+    .await;
+    assert_contains(&buffer, "Count #1 /   0%");
+}
+
+#[tokio::test]
+async fn frames_are_drawn_only_on_render() {
+    let events = [Event::Render]
+        .into_iter()
+        .chain(keys("kk"))
+        .chain([key('q')]);
+    let tui = run_script(events).await.expect("the script runs");
+    assert_contains(tui.backend().buffer(), "Counter: 0");
+}
+
+#[tokio::test]
+async fn script_ending_before_quit_is_an_error() {
+    let result = run_script(keys("kk")).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn recording_file_round_trips() {
+    let path = recording_path("round-trip");
+    let events = [
+        vec![Event::Resize(WIDTH, HEIGHT), Event::Paste("40".to_string())],
+        keys("kkkj"),
+    ]
+    .concat();
+    let mut recorder = Recorder::create(&path).expect("the recording can be created");
+    for event in &events {
+        recorder.record(event).expect("the event can be recorded");
+    }
+    drop(recorder);
+    let replay = Replay::open(&path, true).expect("the recording can be read");
+    fs::remove_file(&path).expect("the recording can be removed");
+
+    assert!(replay
+        .events
+        .windows(2)
+        .all(|pair| pair[0].at_us <= pair[1].at_us));
+    let replayed: Vec<Event> = replay
+        .events
+        .into_iter()
+        .map(|recorded| recorded.event)
+        .collect();
+    assert_eq!(replayed, events);
+    assert_contains(&render_script(replayed).await, "Counter: 42");
+}
+
+#[tokio::test]
+async fn replay_is_recorded_again_and_hands_over_to_the_terminal() {
+    let path = recording_path("replay");
+    let replayed = [
+        vec![Event::Resize(60, HEIGHT), Event::Paste("40".to_string())],
+        keys("kkkj"),
+        vec![Event::Render, key('q')],
+    ]
+    .concat();
+    let session = Session {
+        recorder: Some(Recorder::create(&path).expect("the recording can be created")),
+        replay: Some(replay(replayed.clone(), false)),
+    };
+    let events = EventHandler::with_input(FRAME_RATE, TICK_RATE, session, input(""));
+    let tui = run_events(events).await.expect("the replay runs");
+
+    // The replayed size narrows the interface.
+    let buffer = tui.backend().buffer();
+    assert_contains(buffer, "Counter: 42");
+    assert_eq!(buffer[(59, 0)].symbol(), "╮");
+
+    // The replay starts with its size, so the terminal size is only recorded at the handover.
+    let recorded = take_recording(&path);
+    assert_eq!(recorded[..replayed.len()], replayed[..]);
+    assert_eq!(recorded[replayed.len()], Event::Resize(WIDTH, HEIGHT));
+}
+
+#[tokio::test]
+async fn recording_starts_with_the_terminal_size() {
+    let path = recording_path("live");
+    let session = Session {
+        recorder: Some(Recorder::create(&path).expect("the recording can be created")),
+        replay: None,
+    };
+    let events = EventHandler::with_input(FRAME_RATE, TICK_RATE, session, input("kkq"));
+    run_events(events).await.expect("the session runs");
+
+    let recorded: Vec<Event> = take_recording(&path)
+        .into_iter()
+        .filter(|event| !matches!(event, Event::Tick | Event::Render))
+        .collect();
+    assert_eq!(
+        recorded,
+        [vec![Event::Resize(WIDTH, HEIGHT)], keys("kkq")].concat()
+    );
+}
+
+#[tokio::test]
+async fn real_time_replay_keeps_to_the_recorded_times() {
+    let mut real_time = replay(vec![Event::Render, key('q')], true);
+    real_time.events[1].at_us = 200_000;
+    assert_eq!(
+        real_time.due(&real_time.events[1]),
+        Duration::from_millis(200)
+    );
+    let fast = replay(Vec::new(), false);
+    assert_eq!(fast.due(&real_time.events[1]), Duration::ZERO);
+
+    let session = Session {
+        recorder: None,
+        replay: Some(real_time),
+    };
+    let start = Instant::now();
+    let events = EventHandler::with_input(FRAME_RATE, TICK_RATE, session, input(""));
+    run_events(events).await.expect("the replay runs");
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn quit_key_stops_a_replay() {
+    let mut long = replay(vec![Event::Render, key('k')], true);
+    long.events[1].at_us = 60_000_000;
+    let session = Session {
+        recorder: None,
+        replay: Some(long),
+    };
+    let events = EventHandler::with_input(FRAME_RATE, TICK_RATE, session, input("q"));
+    tokio::time::timeout(Duration::from_secs(5), run_events(events))
+        .await
+        .expect("the replay stops before the recorded end")
+        .expect("the replay runs");
+}
@@ -1,9 +1,13 @@
 use crate::app::{App, AppResult};
 use crate::event::EventHandler;
 use crate::ui;
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::event::{
+    DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+    EnableFocusChange, EnableMouseCapture,
+};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use ratatui::backend::Backend;
+use ratatui::layout::Rect;
 use ratatui::Terminal;
 use std::io;
 use std::panic;
@@ -18,20 +22,31 @@ pub struct Tui<B: Backend> {
     terminal: Terminal<B>,
     /// Terminal event handler.
     pub events: EventHandler,
+    /// The area to draw in, as of the last resize event, `None` for the whole terminal.
+    area: Option<Rect>,
 }
 
 impl<B: Backend> Tui<B> {
     /// Constructs a new instance of [`Tui`].
     pub fn new(terminal: Terminal<B>, events: EventHandler) -> Self {
-        Self { terminal, events }
+        Self {
+            terminal,
+            events,
+            area: None,
+        }
+    }
+
+    /// The backend the interface is drawn on.
+    #[cfg(test)]
+    pub fn backend(&self) -> &B {
+        self.terminal.backend()
     }
 
     /// Initializes the terminal interface.
     ///
     /// It enables the raw mode and sets terminal properties.
     pub fn init(&mut self) -> AppResult<()> {
-        terminal::enable_raw_mode()?;
-        crossterm::execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        self.enter()?;
 
         // Define a custom panic hook to reset the terminal properties.
         // This way, you won't have your terminal messed up if an unexpected error happens.
@@ -40,7 +55,19 @@ impl<B: Backend> Tui<B> {
             Self::reset().expect("failed to reset the terminal");
             panic_hook(panic);
         }));
+        Ok(())
+    }
 
+    /// Sets the terminal properties and clears the screen.
+    fn enter(&mut self) -> AppResult<()> {
+        terminal::enable_raw_mode()?;
+        crossterm::execute!(
+            io::stdout(),
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste,
+            EnableFocusChange
+        )?;
         self.terminal.hide_cursor()?;
         self.terminal.clear()?;
         Ok(())
@@ -51,26 +78,58 @@ impl<B: Backend> Tui<B> {
     /// [`Draw`]: ratatui::Terminal::draw
     /// [`rendering`]: crate::ui::render
     pub fn draw(&mut self, app: &mut App) -> AppResult<()> {
-        self.terminal.draw(|frame| ui::render(app, frame))?;
+        let area = self.area;
+        self.terminal.draw(|frame| {
+            let area = area.map_or(frame.area(), |area| area.intersection(frame.area()));
+            ui::render(app, frame, area);
+        })?;
         Ok(())
     }
 
+    /// Sets the size of the interface, as reported by a resize event.
+    ///
+    /// The terminal follows its actual size anyway, but a replayed session keeps to the
+    /// recorded size, as far as the terminal is large enough.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.area = Some(Rect::new(0, 0, width, height));
+    }
+
     /// Resets the terminal interface.
     ///
     /// This function is also used for the panic hook to revert
     /// the terminal properties if unexpected errors occur.
     fn reset() -> AppResult<()> {
         terminal::disable_raw_mode()?;
-        crossterm::execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+        crossterm::execute!(
+            io::stdout(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            DisableBracketedPaste,
+            DisableFocusChange
+        )?;
         Ok(())
     }
 
+    /// Suspends the process, as `Ctrl-Z` does in a shell.
+    ///
+    /// The terminal properties are reverted before the process stops, and set again once it
+    /// continues on SIGCONT. The next draw then repaints the whole screen.
+    pub fn suspend(&mut self) -> AppResult<()> {
+        Self::reset()?;
+        self.terminal.show_cursor()?;
+        #[cfg(unix)]
+        signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP)?;
+        self.enter()
+    }
+
     /// Exits the terminal interface.
     ///
-    /// It disables the raw mode and reverts back the terminal properties.
-    pub fn exit(&mut self) -> AppResult<()> {
+    /// It stops the event handler, then disables the raw mode and reverts back the terminal
+    /// properties. An error the event handler stopped on is returned after the terminal is reset.
+    pub async fn exit(&mut self) -> AppResult<()> {
+        let stopped = self.events.stop().await;
         Self::reset()?;
         self.terminal.show_cursor()?;
-        Ok(())
+        stopped
     }
 }
@@ -1,5 +1,5 @@
 use ratatui::{
-    layout::Alignment,
+    layout::{Alignment, Constraint, Layout, Rect},
     style::{Color, Style},
     widgets::{Block, BorderType, Paragraph},
     Frame,
@@ -7,28 +7,34 @@ use ratatui::{
 
 use crate::app::App;
 
-/// Renders the user interface widgets.
-pub fn render(app: &mut App, frame: &mut Frame) {
+/// Renders the user interface widgets into `area` of the frame.
+pub fn render(app: &mut App, frame: &mut Frame, area: Rect) {
     // This is where you add new widgets.
     // See the following resources:
     // - https://docs.rs/ratatui/latest/ratatui/widgets/index.html
     // - https://github.com/ratatui/ratatui/tree/master/examples
+    let block = Block::bordered()
+        .title("Template")
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(Color::Cyan).bg(Color::Black));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [help, panes] = Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(inner);
     frame.render_widget(
-        Paragraph::new(format!(
+        Paragraph::new(
             "This is a tui template.\n\
                 Press `Esc`, `Ctrl-C` or `q` to stop running.\n\
-                Press left and right to increment and decrement the counter respectively.\n\
-                Counter: {}",
-            app.counter
-        ))
-        .block(
-            Block::bordered()
-                .title("Template")
-                .title_alignment(Alignment::Center)
-                .border_type(BorderType::Rounded),
+                Press `Tab` to move the focus between the panes.",
         )
-        .style(Style::default().fg(Color::Cyan).bg(Color::Black))
         .centered(),
-        frame.area(),
-    )
+        help,
+    );
+
+    // Each component draws itself into its share of the panes.
+    let areas = Layout::horizontal(vec![Constraint::Fill(1); app.components.len()]).split(panes);
+    for (component, area) in app.components.iter_mut().zip(areas.iter()) {
+        component.draw(frame, *area);
+    }
 }